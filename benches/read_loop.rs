@@ -0,0 +1,120 @@
+// Compares the host-call count of the former fixed 1024-byte read loop with
+// `ReadLoop`, using an in-memory stand-in for the runtime's `*_read` calls.
+//
+//     cargo bench --bench read_loop
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use blockless_sdk::ReadLoop;
+
+struct StandInHost {
+    data: Vec<u8>,
+    pos: usize,
+    calls: usize,
+}
+
+impl StandInHost {
+    fn new(size: usize) -> Self {
+        StandInHost {
+            data: (0..size).map(|i| i as u8).collect(),
+            pos: 0,
+            calls: 0,
+        }
+    }
+
+    fn rewind(&mut self) {
+        self.pos = 0;
+        self.calls = 0;
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+        self.calls += 1;
+        let num = buf.len().min(self.data.len() - self.pos);
+        buf[..num].copy_from_slice(&self.data[self.pos..self.pos + num]);
+        self.pos += num;
+        Ok(num)
+    }
+}
+
+fn legacy_read_all(host: &mut StandInHost) -> Vec<u8> {
+    let mut vec = Vec::new();
+    loop {
+        let mut buf = [0u8; 1024];
+        let num = host.read(&mut buf).unwrap();
+        if num == 0 {
+            break;
+        }
+        vec.extend_from_slice(&buf[..num]);
+    }
+    vec
+}
+
+fn measure(host: &mut StandInHost, iterations: u32, mut f: impl FnMut(&mut StandInHost) -> usize) {
+    let mut elapsed = Duration::ZERO;
+    let mut capacity = 0;
+    for _ in 0..iterations {
+        host.rewind();
+        let started = Instant::now();
+        capacity = black_box(f(host));
+        elapsed += started.elapsed();
+    }
+    println!(
+        "    host calls: {:>6}  capacity: {:>8}  time/iter: {:?}",
+        host.calls,
+        capacity,
+        elapsed / iterations
+    );
+}
+
+fn main() {
+    for size in [64 * 1024, 1024 * 1024, 8 * 1024 * 1024] {
+        let mut host = StandInHost::new(size);
+        let iterations = (64 * 1024 * 1024 / size).clamp(4, 256) as u32;
+        println!("body size {} bytes", size);
+
+        println!("  fixed 1024-byte buffer");
+        measure(&mut host, iterations, |host| {
+            let body = legacy_read_all(host);
+            assert_eq!(body.len(), size);
+            body.capacity()
+        });
+
+        println!("  ReadLoop, adaptive chunks");
+        measure(&mut host, iterations, |host| {
+            let mut body = Vec::new();
+            ReadLoop::new()
+                .read_to_end(&mut body, |buf| host.read(buf))
+                .unwrap();
+            assert_eq!(body.len(), size);
+            body.capacity()
+        });
+
+        println!("  ReadLoop, size hint");
+        measure(&mut host, iterations, |host| {
+            let mut read_loop = ReadLoop::new();
+            read_loop.set_size_hint(size as u64);
+            let mut body = Vec::new();
+            read_loop
+                .read_to_end(&mut body, |buf| host.read(buf))
+                .unwrap();
+            assert_eq!(body.len(), size);
+            body.capacity()
+        });
+
+        println!("  ReadLoop, size hint, caller supplied buffer");
+        let mut body = Vec::with_capacity(size);
+        measure(&mut host, iterations, |host| {
+            body.clear();
+            let mut read_loop = ReadLoop::new();
+            read_loop.set_size_hint(size as u64);
+            read_loop
+                .read_to_end(&mut body, |buf| host.read(buf))
+                .unwrap();
+            assert_eq!(body.len(), size);
+            body.capacity()
+        });
+    }
+}
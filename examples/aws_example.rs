@@ -2,36 +2,8 @@ use blockless_sdk::*;
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
-    //
-    // This can be used in with a actual project using the
-    // blockless rust sdk, involving the bls command
-    //
-    // let mut env_buf = [0u8; 4096];
-    // let config = Vec::new();
-    // match read_env_vars(&mut env_buf) {
-    //     Ok(len) => {
-    //         let env_vars = String::from_utf8_lossy(&env_buf[..len as usize]);
-    //         for var in env_vars.split('\0') {
-    //             if !var.is_empty() {
-    //                 if let Some((_key, value)) = var.split_once(':') {
-    //                     let cleaned_value = value
-    //                         .trim()
-    //                         .trim_matches(|c| c == '"' || c == '{' || c == '}')
-    //                         .trim();
-
-    //                     for part in cleaned_value.split(',') {
-    //                         config.push(part.trim().to_string());
-    //                     }
-    //                 } else {
-    //                     println!("{}", var);
-    //                 }
-    //             }
-    //         }
-    //     }
-    //     Err(e) => eprintln!("Error reading environment variables: {}", e),
-    // }
-
-    let config = std::env::var("CONFIG").unwrap();
+    // CONFIG is passed with `--env "CONFIG"="BUCKET_NAME,ACCESS_KEY,SECRET_KEY,REGION"`
+    let config = env::var("CONFIG")?;
     let config: Vec<&str> = config.split(',').collect();
     let bucket_name = config[0].to_string();
     let access_key = config[1].to_string();
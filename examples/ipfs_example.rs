@@ -20,7 +20,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         parents: true,
         truncate: false,
     };
-    match ipfs_file_write(&write_opts, content.clone().as_mut()) {
+    match ipfs_file_write(&write_opts, content) {
         Ok(true) => println!("File '{}' written successfully", file_path),
         Ok(false) => println!("Failed to write file '{}'", file_path),
         Err(e) => println!("Error writing file: {:?}", e),
@@ -36,7 +36,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         parents: false,
         truncate: false,
     };
-    match ipfs_file_write(&edit_opts, content.clone().as_mut()) {
+    match ipfs_file_write(&edit_opts, content) {
         Ok(true) => println!("File {} edited successfully", file_path),
         Ok(false) => println!("Failed to write file '{}'", file_path),
         Err(e) => println!("Error warning file: {:?}", e),
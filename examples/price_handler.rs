@@ -0,0 +1,28 @@
+use blockless_sdk::env::Env;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+struct Request {
+    coin_id: String,
+    amount: f64,
+}
+
+#[derive(Serialize)]
+struct Quote {
+    id: String,
+    currency: String,
+    total: f64,
+}
+
+// Run with `{"coin_id": "bitcoin", "amount": 2}` on stdin and the
+// `PRICE` and `CURRENCY` environment variables set.
+#[blockless_sdk::main]
+fn main(input: Request, env: Env) -> Result<Quote, String> {
+    let price: f64 = env.require_parse("PRICE").map_err(|e| e.to_string())?;
+    let currency = env.get("CURRENCY").unwrap_or("usd");
+    Ok(Quote {
+        id: input.coin_id,
+        currency: currency.to_string(),
+        total: price * input.amount,
+    })
+}
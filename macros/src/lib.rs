@@ -0,0 +1,103 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Error, Ident, ItemFn, LitStr, ReturnType, Type};
+
+// Turns `fn handler(input: T, env: Env) -> Result<O, E>` into the function
+// entrypoint: stdin is decoded into `T`, the environment is loaded, and the
+// handler result is written as an output envelope. See
+// `blockless_sdk::entry::run_with` for the exit codes.
+//
+//     #[blockless_sdk::main]
+//     #[blockless_sdk::main(input = "json", output = "cbor")]
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut input_format = Ident::new("Auto", Span::call_site());
+    let mut output_format = Ident::new("Json", Span::call_site());
+    let parser = syn::meta::parser(|meta| {
+        let value: LitStr = meta.value()?.parse()?;
+        let format = match value.value().as_str() {
+            "auto" if meta.path.is_ident("input") => "Auto",
+            "json" => "Json",
+            "cbor" => "Cbor",
+            "msgpack" if meta.path.is_ident("input") => "MessagePack",
+            _ => return Err(meta.error(format!("unsupported format `{}`", value.value()))),
+        };
+        if meta.path.is_ident("input") {
+            input_format = Ident::new(format, value.span());
+        } else if meta.path.is_ident("output") {
+            output_format = Ident::new(format, value.span());
+        } else {
+            return Err(meta.error("expected `input` or `output`"));
+        }
+        Ok(())
+    });
+    parse_macro_input!(args with parser);
+
+    let mut handler = parse_macro_input!(item as ItemFn);
+    if let Err(e) = check_signature(&handler) {
+        return e.to_compile_error().into();
+    }
+    if handler.sig.ident == "main" {
+        handler.sig.ident = Ident::new("__blockless_main", handler.sig.ident.span());
+    }
+    let name = &handler.sig.ident;
+    quote! {
+        #handler
+
+        fn main() -> ::std::process::ExitCode {
+            ::blockless_sdk::entry::run_with(
+                ::blockless_sdk::input::Format::#input_format,
+                ::blockless_sdk::output::Format::#output_format,
+                #name,
+            )
+        }
+    }
+    .into()
+}
+
+fn check_signature(handler: &ItemFn) -> Result<(), Error> {
+    let sig = &handler.sig;
+    if let Some(asyncness) = sig.asyncness {
+        return Err(Error::new(asyncness.span(), "the handler cannot be async"));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "the handler cannot be generic",
+        ));
+    }
+    if sig.inputs.len() != 2 {
+        return Err(Error::new(
+            sig.inputs.span(),
+            "expected `fn(input: T, env: Env) -> Result<O, E>`",
+        ));
+    }
+    if !returns_result(&sig.output) {
+        let span = match &sig.output {
+            ReturnType::Type(_, ty) => ty.span(),
+            ReturnType::Default => sig.paren_token.span.close(),
+        };
+        return Err(Error::new(
+            span,
+            "expected `fn(input: T, env: Env) -> Result<O, E>`",
+        ));
+    }
+    Ok(())
+}
+
+// Only the last path segment is checked, so `io::Result<T>` and other
+// aliases named `Result` are accepted.
+fn returns_result(output: &ReturnType) -> bool {
+    let ReturnType::Type(_, ty) = output else {
+        return false;
+    };
+    match &**ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Result"),
+        _ => false,
+    }
+}
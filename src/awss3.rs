@@ -2,7 +2,8 @@ use json::{object, JsonValue};
 
 use crate::{
     awss3_host::{bucket_command, bucket_put_object, s3_close, s3_read},
-    AWSS3ErrorKind,
+    permissions::{self, Permission},
+    AWSS3ErrorKind, ReadLoop, Secret,
 };
 
 fn read_body(h: u32, buf: &mut [u8]) -> Result<usize, AWSS3ErrorKind> {
@@ -16,14 +17,7 @@ fn read_body(h: u32, buf: &mut [u8]) -> Result<usize, AWSS3ErrorKind> {
 
 fn get_body(h: u32) -> Result<Vec<u8>, AWSS3ErrorKind> {
     let mut result = Vec::new();
-    let mut buf = vec![0; 1024];
-    loop {
-        let num = read_body(h, &mut buf)?;
-        if num == 0 {
-            break;
-        }
-        result.extend_from_slice(&buf[..num]);
-    }
+    ReadLoop::new().read_to_end(&mut result, |buf| read_body(h, buf))?;
     Ok(result)
 }
 
@@ -90,10 +84,10 @@ impl std::fmt::Display for BucketListResponse {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct AWSS3Configure {
-    pub access_key: String,
-    pub secret_key: String,
+    pub access_key: Secret<String>,
+    pub secret_key: Secret<String>,
     pub endpoint: String,
     pub region: Option<String>,
 }
@@ -101,8 +95,8 @@ pub struct AWSS3Configure {
 impl AWSS3Configure {
     pub fn new(access_key: String, secret_key: String, endpoint: String) -> Self {
         AWSS3Configure {
-            access_key,
-            secret_key,
+            access_key: Secret::new(access_key),
+            secret_key: Secret::new(secret_key),
             endpoint,
             region: None,
         }
@@ -114,8 +108,8 @@ impl AWSS3Configure {
 
     pub fn encode_json(&self) -> String {
         let mut obj = object! {
-            "access_key" : self.access_key.clone(),
-            "secret_key": self.secret_key.clone(),
+            "access_key" : self.access_key.expose_secret().clone(),
+            "secret_key": self.secret_key.expose_secret().clone(),
             "endpoint": self.endpoint.clone(),
         };
 
@@ -156,8 +150,8 @@ impl BucketCommand {
     }
 
     pub fn to_json(&self) -> String {
-        let mut json_value =
-            json::parse(&self.config.encode_json()).unwrap_or(json::JsonValue::Null);
+        let config = Secret::new(self.config.encode_json());
+        let mut json_value = json::parse(config.expose_secret()).unwrap_or(json::JsonValue::Null);
 
         if let JsonValue::Object(obj) = &mut json_value {
             for arg in &self.args {
@@ -168,7 +162,7 @@ impl BucketCommand {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Bucket {
     pub bucket_name: String,
     pub s3config: AWSS3Configure,
@@ -183,9 +177,11 @@ impl Bucket {
     }
 
     pub fn list(self, prefix: &str) -> Result<Vec<BucketListResponse>, AWSS3ErrorKind> {
+        self.require_permission()?;
         let mut cmd = self.get_bucket_command();
         cmd.add_arg("prefix".to_string(), prefix.to_string());
-        let command = cmd.to_json();
+        let command = Secret::new(cmd.to_json());
+        let command = command.expose_secret();
         let mut handle: u32 = 0;
         let rs = unsafe { bucket_command(2, command.as_ptr(), command.len() as u32, &mut handle) };
         if rs != 0 {
@@ -238,6 +234,11 @@ impl Bucket {
         Ok(result)
     }
 
+    fn require_permission(&self) -> Result<(), AWSS3ErrorKind> {
+        permissions::require(Permission::S3Bucket(self.bucket_name.clone()))
+            .map_err(AWSS3ErrorKind::MissingPermission)
+    }
+
     pub fn get_bucket_command(self) -> BucketCommand {
         let mut cmd = BucketCommand::new(self.s3config);
         cmd.add_arg("bucket_name".to_string(), self.bucket_name);
@@ -245,9 +246,23 @@ impl Bucket {
     }
 
     pub fn get_object(self, path: &str) -> Result<Vec<u8>, AWSS3ErrorKind> {
+        let mut data = Vec::new();
+        self.get_object_into(path, &ReadLoop::new(), &mut data)?;
+        Ok(data)
+    }
+
+    // Appends the object to `buf` and returns its size.
+    pub fn get_object_into(
+        self,
+        path: &str,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, AWSS3ErrorKind> {
+        self.require_permission()?;
         let mut cmd = self.get_bucket_command();
         cmd.add_arg("path".to_string(), path.to_string());
-        let command = cmd.to_json();
+        let command = Secret::new(cmd.to_json());
+        let command = command.expose_secret();
         let mut handle: u32 = 0;
 
         let rs = unsafe { bucket_command(3, command.as_ptr(), command.len() as u32, &mut handle) };
@@ -255,18 +270,21 @@ impl Bucket {
         if rs != 0 {
             return Err(AWSS3ErrorKind::from(rs));
         }
-        let bs = get_body(handle)?;
+        let result = read_loop.read_to_end(buf, |chunk| read_body(handle, chunk));
         let rs = unsafe { s3_close(handle) };
+        let num = result?;
         if rs != 0 {
             return Err(AWSS3ErrorKind::from(rs));
         }
-        Ok(bs)
+        Ok(num)
     }
 
     pub fn put_object(self, path: &str, content: &mut [u8]) -> Result<bool, AWSS3ErrorKind> {
+        self.require_permission()?;
         let mut cmd = self.get_bucket_command();
         cmd.add_arg("path".to_string(), path.to_string());
-        let command = cmd.to_json();
+        let command = Secret::new(cmd.to_json());
+        let command = command.expose_secret();
 
         let rs = unsafe {
             bucket_put_object(
@@ -284,9 +302,11 @@ impl Bucket {
     }
 
     pub fn delete_object(self, path: &str) -> Result<bool, AWSS3ErrorKind> {
+        self.require_permission()?;
         let mut cmd = self.get_bucket_command();
         cmd.add_arg("path".to_string(), path.to_string());
-        let command = cmd.to_json();
+        let command = Secret::new(cmd.to_json());
+        let command = command.expose_secret();
         let mut handle = 0u32;
 
         let rs = unsafe { bucket_command(4, command.as_ptr(), command.len() as u32, &mut handle) };
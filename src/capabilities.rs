@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+pub const MEMORY_MODULE: &str = "blockless_memory";
+pub const HTTP_MODULE: &str = "blockless_http";
+pub const IPFS_MODULE: &str = "blockless_ipfs";
+pub const S3_MODULE: &str = "blockless_s3";
+pub const CGI_MODULE: &str = "blockless_cgi";
+pub const SOCKET_MODULE: &str = "blockless_socket";
+
+// The host modules this build can import, following the enabled cargo
+// features.
+pub const COMPILED_MODULES: &[&str] = &[
+    MEMORY_MODULE,
+    #[cfg(feature = "http")]
+    HTTP_MODULE,
+    #[cfg(feature = "ipfs")]
+    IPFS_MODULE,
+    #[cfg(feature = "s3")]
+    S3_MODULE,
+    #[cfg(feature = "cgi")]
+    CGI_MODULE,
+    #[cfg(feature = "socket")]
+    SOCKET_MODULE,
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct Capabilities {
+    reported: bool,
+    modules: BTreeMap<String, Option<u32>>,
+    ipfs_commands: Option<Vec<String>>,
+    s3_commands: Option<Vec<String>>,
+}
+
+impl Capabilities {
+    // The modules this build was compiled to use, from its cargo features.
+    // The runtime can't be probed: it doesn't report its capabilities, and
+    // the linker drops imports that are never called, so a module listed
+    // here may not be imported at all and its presence is not proven by the
+    // function having been instantiated. Versions and commands are unknown.
+    pub fn detect() -> Self {
+        let modules = COMPILED_MODULES
+            .iter()
+            .map(|module| (module.to_string(), None))
+            .collect();
+        Capabilities {
+            reported: false,
+            modules,
+            ipfs_commands: None,
+            s3_commands: None,
+        }
+    }
+
+    // Reads a capability report obtained some other way, e.g. from the
+    // function's input:
+    //
+    //     {
+    //         "modules": {"blockless_http": 1, "blockless_ipfs": 2, ...},
+    //         "ipfs": ["files_ls", "files_read", ...],
+    //         "s3": ["list", "get", "put", ...]
+    //     }
+    pub fn parse(report: &str) -> Option<Self> {
+        let report = json::parse(report).ok()?;
+        if !report.is_object() {
+            return None;
+        }
+        let mut modules = BTreeMap::new();
+        for (name, version) in report["modules"].entries() {
+            modules.insert(name.to_string(), version.as_u32());
+        }
+        Some(Capabilities {
+            reported: true,
+            modules,
+            ipfs_commands: commands(&report["ipfs"]),
+            s3_commands: commands(&report["s3"]),
+        })
+    }
+
+    // Whether the capabilities come from a report rather than from the
+    // imports of this build.
+    pub fn is_reported(&self) -> bool {
+        self.reported
+    }
+
+    pub fn has_module(&self, module: &str) -> bool {
+        self.modules.contains_key(module)
+    }
+
+    pub fn abi_version(&self, module: &str) -> Option<u32> {
+        self.modules.get(module).copied().flatten()
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = (&str, Option<u32>)> {
+        self.modules
+            .iter()
+            .map(|(module, version)| (module.as_str(), *version))
+    }
+
+    pub fn ipfs_commands(&self) -> Option<&[String]> {
+        self.ipfs_commands.as_deref()
+    }
+
+    pub fn s3_commands(&self) -> Option<&[String]> {
+        self.s3_commands.as_deref()
+    }
+
+    // `None` when the runtime did not report its commands.
+    pub fn supports_ipfs_command(&self, command: &str) -> Option<bool> {
+        self.ipfs_commands()
+            .map(|commands| commands.iter().any(|c| c == command))
+    }
+
+    pub fn supports_s3_command(&self, command: &str) -> Option<bool> {
+        self.s3_commands()
+            .map(|commands| commands.iter().any(|c| c == command))
+    }
+}
+
+fn commands(value: &json::JsonValue) -> Option<Vec<String>> {
+    if !value.is_array() {
+        return None;
+    }
+    Some(
+        value
+            .members()
+            .filter_map(|command| command.as_str())
+            .map(String::from)
+            .collect(),
+    )
+}
+
+pub fn detect() -> Capabilities {
+    Capabilities::detect()
+}
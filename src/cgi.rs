@@ -2,7 +2,11 @@ use std::fmt::{Debug, Display};
 
 use json::{object::Object, JsonValue};
 
-use crate::{cgi_host::*, CGIErrorKind};
+use crate::{
+    cgi_host::*,
+    permissions::{self, Permission},
+    CGIErrorKind, ReadLoop,
+};
 
 #[derive(Debug)]
 pub struct CGIExtensions {
@@ -33,7 +37,7 @@ pub struct CGICommand {
     handle: Option<u32>,
 }
 
-type ReadFn = unsafe extern "C" fn(u32, *mut u8, u32, *mut u32) -> u32;
+type ReadFn = unsafe fn(u32, *mut u8, u32, *mut u32) -> u32;
 
 impl CGICommand {
     fn new(command: String, args: Vec<String>, envs: Vec<CGIEnv>) -> Self {
@@ -51,42 +55,59 @@ impl CGICommand {
         unsafe {
             let rs = cgi_open(parmas.as_ptr(), parmas.len() as _, &mut handle);
             if rs != 0 {
-                return Err(CGIErrorKind::ExecError);
+                return Err(CGIErrorKind::ExecError(rs));
             }
         };
         self.handle = Some(handle);
         Ok(())
     }
 
-    fn read_all(&mut self, read_call: ReadFn) -> Result<Vec<u8>, CGIErrorKind> {
-        let mut readn = 0u32;
-        let mut data: Vec<u8> = Vec::new();
-        if self.handle.is_none() {
-            return Ok(data);
-        }
-        let handle = self.handle.unwrap();
-        let mut bs = [0u8; 1024];
-        loop {
-            unsafe {
-                let rs = read_call(handle, &mut bs as _, bs.len() as _, &mut readn);
-                if rs != 0 {
-                    return Err(CGIErrorKind::ReadError);
-                }
-                if readn == 0 {
-                    break;
-                }
-                data.extend_from_slice(&bs[..readn as _]);
+    fn read_all_into(
+        &mut self,
+        read_call: ReadFn,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, CGIErrorKind> {
+        let Some(handle) = self.handle else {
+            return Ok(0);
+        };
+        read_loop.read_to_end(buf, |bs| {
+            let mut readn = 0u32;
+            let rs = unsafe { read_call(handle, bs.as_mut_ptr(), bs.len() as _, &mut readn) };
+            if rs != 0 {
+                return Err(CGIErrorKind::ReadError(rs));
             }
-        }
-        Ok(data)
+            Ok(readn as usize)
+        })
     }
 
     pub fn read_all_stdin(&mut self) -> Result<Vec<u8>, CGIErrorKind> {
-        self.read_all(cgi_stdout_read)
+        let mut data = Vec::new();
+        self.read_all_stdin_into(&ReadLoop::new(), &mut data)?;
+        Ok(data)
+    }
+
+    // Appends the command's stdout to `buf`.
+    pub fn read_all_stdin_into(
+        &mut self,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, CGIErrorKind> {
+        self.read_all_into(cgi_stdout_read, read_loop, buf)
     }
 
     pub fn read_all_stderr(&mut self) -> Result<Vec<u8>, CGIErrorKind> {
-        self.read_all(cgi_stderr_read)
+        let mut data = Vec::new();
+        self.read_all_stderr_into(&ReadLoop::new(), &mut data)?;
+        Ok(data)
+    }
+
+    pub fn read_all_stderr_into(
+        &mut self,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, CGIErrorKind> {
+        self.read_all_into(cgi_stderr_read, read_loop, buf)
     }
 
     pub fn exec_command(&mut self) -> Result<String, CGIErrorKind> {
@@ -140,7 +161,7 @@ impl CGIListExtensions {
         unsafe {
             let rs = cgi_list_exec(&mut cgi_handle as *mut u32);
             if rs != 0 {
-                return Err(CGIErrorKind::ListError);
+                return Err(CGIErrorKind::ListError(rs));
             }
         };
         Ok(CGIListExtensions { handle: cgi_handle })
@@ -148,20 +169,15 @@ impl CGIListExtensions {
 
     fn list_read_all(&self) -> Result<Vec<u8>, CGIErrorKind> {
         let mut data: Vec<u8> = Vec::new();
-        let mut bs = [0u8; 1024];
-        let mut readn = 0u32;
-        loop {
-            unsafe {
-                let rs = cgi_list_read(self.handle, &mut bs as _, bs.len() as _, &mut readn);
-                if rs != 0 {
-                    return Err(CGIErrorKind::ListError);
-                }
-                if readn == 0 {
-                    break;
-                }
-                data.extend_from_slice(&bs[..readn as _]);
+        ReadLoop::new().read_to_end(&mut data, |bs| {
+            let mut readn = 0u32;
+            let rs =
+                unsafe { cgi_list_read(self.handle, bs.as_mut_ptr(), bs.len() as _, &mut readn) };
+            if rs != 0 {
+                return Err(CGIErrorKind::ListError(rs));
             }
-        }
+            Ok(readn as usize)
+        })?;
         Ok(data)
     }
 
@@ -171,10 +187,12 @@ impl CGIListExtensions {
         args: Vec<String>,
         envs: Vec<CGIEnv>,
     ) -> Result<CGICommand, CGIErrorKind> {
+        permissions::require(Permission::CgiAlias(command.to_string()))
+            .map_err(CGIErrorKind::MissingPermission)?;
         let extensions = self.list()?;
         extensions
             .iter()
-            .find(|ext| if &ext.alias == command { true } else { false })
+            .find(|ext| ext.alias == command)
             .map(|_| CGICommand::new(command.to_string(), args, envs))
             .ok_or(CGIErrorKind::NoCommandError)
     }
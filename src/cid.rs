@@ -0,0 +1,361 @@
+use std::{fmt, str::FromStr};
+
+use crate::{sha256::Sha256, CidErrorKind};
+
+// Multicodec codes of the content a CID points to.
+pub const CODEC_RAW: u64 = 0x55;
+pub const CODEC_DAG_PB: u64 = 0x70;
+pub const CODEC_DAG_CBOR: u64 = 0x71;
+pub const CODEC_LIBP2P_KEY: u64 = 0x72;
+pub const CODEC_DAG_JSON: u64 = 0x0129;
+
+// Multihash function codes. Only sha2-256 can be computed locally; digests
+// of other functions are kept as the node returned them.
+pub const SHA2_256: u64 = 0x12;
+
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Multihash {
+    code: u64,
+    digest: Vec<u8>,
+}
+
+impl Multihash {
+    pub fn new(code: u64, digest: &[u8]) -> Result<Self, CidErrorKind> {
+        if code == SHA2_256 && digest.len() != 32 {
+            return Err(CidErrorKind::InvalidMultihash);
+        }
+        if digest.len() > u8::MAX as usize {
+            return Err(CidErrorKind::InvalidMultihash);
+        }
+        Ok(Multihash {
+            code,
+            digest: digest.to_vec(),
+        })
+    }
+
+    pub fn sha2_256(data: &[u8]) -> Self {
+        Multihash {
+            code: SHA2_256,
+            digest: Sha256::digest(data).to_vec(),
+        }
+    }
+
+    pub fn code(&self) -> u64 {
+        self.code
+    }
+
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    // Reads a multihash from the start of `bytes`, returning it with the
+    // number of bytes it took.
+    pub fn read_bytes(bytes: &[u8]) -> Result<(Self, usize), CidErrorKind> {
+        let (code, n) = read_varint(bytes)?;
+        let (len, m) = read_varint(&bytes[n..])?;
+        let start = n + m;
+        let end = start
+            .checked_add(len as usize)
+            .filter(|end| *end <= bytes.len())
+            .ok_or(CidErrorKind::InvalidMultihash)?;
+        Ok((Multihash::new(code, &bytes[start..end])?, end))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.digest.len() + 4);
+        write_varint(&mut bytes, self.code);
+        write_varint(&mut bytes, self.digest.len() as u64);
+        bytes.extend_from_slice(&self.digest);
+        bytes
+    }
+}
+
+impl fmt::Debug for Multihash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Multihash({:#x}, ", self.code)?;
+        for b in &self.digest {
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Version {
+    V0,
+    V1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base {
+    // `b` prefix, the default for CIDv1
+    Base32Lower,
+    // `z` prefix; CIDv0 is base58btc without the prefix
+    Base58Btc,
+}
+
+// A content identifier: `Qm...` (v0) or `bafy...` (v1). Equality compares
+// version, codec and multihash, so a CID still equals itself after a
+// round-trip through another multibase.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cid {
+    version: u8,
+    codec: u64,
+    hash: Multihash,
+}
+
+impl Cid {
+    // CIDv0 only exists for dag-pb content hashed with sha2-256.
+    pub fn new_v0(hash: Multihash) -> Result<Self, CidErrorKind> {
+        if hash.code != SHA2_256 {
+            return Err(CidErrorKind::InvalidV0);
+        }
+        Ok(Cid {
+            version: 0,
+            codec: CODEC_DAG_PB,
+            hash,
+        })
+    }
+
+    pub fn new_v1(codec: u64, hash: Multihash) -> Self {
+        Cid {
+            version: 1,
+            codec,
+            hash,
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        match self.version {
+            0 => Version::V0,
+            _ => Version::V1,
+        }
+    }
+
+    pub fn codec(&self) -> u64 {
+        self.codec
+    }
+
+    pub fn hash(&self) -> &Multihash {
+        &self.hash
+    }
+
+    pub fn into_v1(self) -> Self {
+        Cid::new_v1(self.codec, self.hash)
+    }
+
+    pub fn into_v0(self) -> Result<Self, CidErrorKind> {
+        if self.codec != CODEC_DAG_PB {
+            return Err(CidErrorKind::InvalidV0);
+        }
+        Cid::new_v0(self.hash)
+    }
+
+    pub fn read_bytes(bytes: &[u8]) -> Result<(Self, usize), CidErrorKind> {
+        // a v0 CID is a bare sha2-256 multihash
+        if bytes.len() >= 34 && bytes[0] == 0x12 && bytes[1] == 0x20 {
+            let (hash, n) = Multihash::read_bytes(bytes)?;
+            return Ok((Cid::new_v0(hash)?, n));
+        }
+        let (version, n) = read_varint(bytes)?;
+        if version != 1 {
+            return Err(CidErrorKind::UnsupportedVersion(version));
+        }
+        let (codec, m) = read_varint(&bytes[n..])?;
+        let (hash, k) = Multihash::read_bytes(&bytes[n + m..])?;
+        Ok((Cid::new_v1(codec, hash), n + m + k))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CidErrorKind> {
+        let (cid, n) = Cid::read_bytes(bytes)?;
+        if n != bytes.len() {
+            return Err(CidErrorKind::InvalidLength);
+        }
+        Ok(cid)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if self.version == 0 {
+            return self.hash.to_bytes();
+        }
+        let mut bytes = Vec::with_capacity(self.hash.digest.len() + 8);
+        write_varint(&mut bytes, 1);
+        write_varint(&mut bytes, self.codec);
+        bytes.extend_from_slice(&self.hash.to_bytes());
+        bytes
+    }
+
+    pub fn parse(s: &str) -> Result<Self, CidErrorKind> {
+        if s.len() == 46 && s.starts_with("Qm") {
+            let bytes = base58btc_decode(s).ok_or(CidErrorKind::InvalidMultibase)?;
+            return Cid::from_bytes(&bytes);
+        }
+        let mut chars = s.chars();
+        let bytes = match chars.next() {
+            Some('b') => base32_decode(chars.as_str(), false),
+            Some('B') => base32_decode(chars.as_str(), true),
+            Some('z') => base58btc_decode(chars.as_str()),
+            Some(prefix) => return Err(CidErrorKind::UnsupportedMultibase(prefix)),
+            None => return Err(CidErrorKind::InvalidLength),
+        }
+        .ok_or(CidErrorKind::InvalidMultibase)?;
+        let cid = Cid::from_bytes(&bytes)?;
+        if cid.version == 0 {
+            // v0 has no multibase prefix
+            return Err(CidErrorKind::InvalidV0);
+        }
+        Ok(cid)
+    }
+
+    // Formats a CIDv1 in `base`; a CIDv0 is always `Qm...` base58btc.
+    pub fn to_string_of_base(&self, base: Base) -> String {
+        let bytes = self.to_bytes();
+        if self.version == 0 {
+            return base58btc_encode(&bytes);
+        }
+        match base {
+            Base::Base32Lower => format!("b{}", base32_encode(&bytes)),
+            Base::Base58Btc => format!("z{}", base58btc_encode(&bytes)),
+        }
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_of_base(Base::Base32Lower))
+    }
+}
+
+impl fmt::Debug for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cid({})", self)
+    }
+}
+
+impl FromStr for Cid {
+    type Err = CidErrorKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Cid::parse(s)
+    }
+}
+
+fn read_varint(bytes: &[u8]) -> Result<(u64, usize), CidErrorKind> {
+    let mut value = 0u64;
+    for (i, b) in bytes.iter().enumerate().take(9) {
+        value |= ((b & 0x7f) as u64) << (7 * i);
+        if b & 0x80 == 0 {
+            // the shortest encoding is the only valid one
+            if i > 0 && *b == 0 {
+                return Err(CidErrorKind::InvalidVarint);
+            }
+            return Ok((value, i + 1));
+        }
+    }
+    Err(CidErrorKind::InvalidVarint)
+}
+
+pub(crate) fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for b in bytes {
+        buffer = (buffer << 8) | *b as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+// Only the canonical encoding is accepted: a single case, a length an
+// encoder can produce, and zero bits after the last byte.
+fn base32_decode(s: &str, upper: bool) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let c = match upper {
+            true if c.is_ascii_lowercase() => return None,
+            true => c.to_ascii_lowercase(),
+            false => c,
+        };
+        let value = BASE32_ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = ((buffer << 5) | value) & 0xfff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    // a full character left over means a length of 1, 3 or 6 modulo 8
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn base58btc_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    // base-58 digits, least significant first
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for b in &bytes[zeros..] {
+        let mut carry = *b as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.extend(std::iter::repeat_n('1', zeros));
+    out.extend(
+        digits
+            .iter()
+            .rev()
+            .map(|d| BASE58_ALPHABET[*d as usize] as char),
+    );
+    out
+}
+
+fn base58btc_decode(s: &str) -> Option<Vec<u8>> {
+    let zeros = s.bytes().take_while(|c| *c == b'1').count();
+    // bytes, least significant first
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len());
+    for c in s.bytes().skip(zeros) {
+        let mut carry = BASE58_ALPHABET.iter().position(|a| *a == c)? as u32;
+        for b in bytes.iter_mut() {
+            carry += (*b as u32) * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut out = vec![0u8; zeros];
+    out.extend(bytes.iter().rev());
+    Some(out)
+}
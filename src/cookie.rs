@@ -0,0 +1,300 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{url::Url, BlocklessHttp, HttpErrorKind, HttpOptions};
+
+// Longest lifetime a cookie gets, as RFC 6265bis caps `Max-Age`.
+pub const MAX_COOKIE_AGE: Duration = Duration::from_secs(400 * 86400);
+
+#[derive(Clone, Debug)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub host_only: bool,
+    pub path: String,
+    pub expires: Option<SystemTime>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+impl Cookie {
+    // Parses a single `Set-Cookie` value received from `url`. Returns `None`
+    // when the cookie is malformed or its attributes are not allowed for the
+    // origin (e.g. a `Domain` the host does not belong to, or `Secure` over
+    // http).
+    pub fn parse(set_cookie: &str, url: &str) -> Option<Self> {
+        let url = Url::parse(url)?;
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: url.host.clone(),
+            host_only: true,
+            path: default_path(url.path).to_string(),
+            expires: None,
+            secure: false,
+            http_only: false,
+        };
+        let mut max_age = None;
+        for attr in parts {
+            let (key, val) = attr.split_once('=').unwrap_or((attr, ""));
+            let val = val.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" => {
+                    // an empty domain, also `Domain=.`, is ignored
+                    let domain = val.strip_prefix('.').unwrap_or(val).to_ascii_lowercase();
+                    if domain.is_empty() {
+                        continue;
+                    }
+                    if !domain_match(&url.host, &domain) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if val.starts_with('/') => cookie.path = val.to_string(),
+                "expires" => {
+                    if let Some(expires) = parse_http_date(val) {
+                        cookie.expires = Some(expires);
+                    }
+                }
+                "max-age" => max_age = val.parse::<i64>().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                _ => {}
+            }
+        }
+        // only a secure origin may set a `Secure` cookie
+        if cookie.secure && !url.is_secure() {
+            return None;
+        }
+        // Max-Age wins over Expires when both are present.
+        if let Some(max_age) = max_age {
+            cookie.expires = if max_age <= 0 {
+                Some(UNIX_EPOCH)
+            } else {
+                let max_age = Duration::from_secs(max_age as u64).min(MAX_COOKIE_AGE);
+                SystemTime::now().checked_add(max_age)
+            };
+        }
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        matches!(self.expires, Some(expires) if expires <= now)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let domain_ok = if self.host_only {
+            url.host == self.domain
+        } else {
+            domain_match(&url.host, &self.domain)
+        };
+        domain_ok && path_match(url.path, &self.path) && (!self.secure || url.is_secure())
+    }
+}
+
+#[derive(Default)]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, cookie: Cookie) {
+        self.cookies.retain(|c| {
+            !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+        });
+        if !cookie.is_expired(SystemTime::now()) {
+            self.cookies.push(cookie);
+        }
+    }
+
+    // Stores every cookie of a `Set-Cookie` header value received from `url`.
+    // Several cookies folded into one value with commas are split apart.
+    pub fn store(&mut self, set_cookie: &str, url: &str) {
+        for value in split_set_cookie(set_cookie) {
+            if let Some(cookie) = Cookie::parse(&value, url) {
+                self.insert(cookie);
+            }
+        }
+    }
+
+    // The `Cookie` header value to send to `url`, if any cookie matches.
+    pub fn cookie_header(&self, url: &str) -> Option<String> {
+        let url = Url::parse(url)?;
+        let now = SystemTime::now();
+        let mut matching = self
+            .cookies
+            .iter()
+            .filter(|c| !c.is_expired(now) && c.matches(&url))
+            .collect::<Vec<_>>();
+        if matching.is_empty() {
+            return None;
+        }
+        // more specific paths first, as browsers do
+        matching.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        let header = matching
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        Some(header)
+    }
+
+    pub fn remove_expired(&mut self) {
+        let now = SystemTime::now();
+        self.cookies.retain(|c| !c.is_expired(now));
+    }
+
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cookie> {
+        self.cookies.iter()
+    }
+}
+
+// Sends requests with the cookies collected from earlier responses.
+#[derive(Default)]
+pub struct HttpSession {
+    pub jar: CookieJar,
+}
+
+impl HttpSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self, url: &str, opts: &HttpOptions) -> Result<BlocklessHttp, HttpErrorKind> {
+        let mut opts = opts.clone();
+        if let Some(cookie) = self.jar.cookie_header(url) {
+            opts.set_header("Cookie", &cookie);
+        }
+        let http = BlocklessHttp::open(url, &opts)?;
+        match http.get_header("Set-Cookie") {
+            Ok(set_cookie) => self.jar.store(&set_cookie, url),
+            Err(HttpErrorKind::HeaderNotFound) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(http)
+    }
+}
+
+fn split_set_cookie(header: &str) -> Vec<String> {
+    let mut cookies: Vec<String> = Vec::new();
+    for part in header.split(',') {
+        match cookies.last_mut() {
+            // the comma after the weekday of an `Expires` date
+            Some(last) if ends_in_expires_weekday(last) => {
+                last.push(',');
+                last.push_str(part);
+            }
+            _ => cookies.push(part.to_string()),
+        }
+    }
+    cookies
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+fn ends_in_expires_weekday(cookie: &str) -> bool {
+    let last_attr = cookie.rsplit(';').next().unwrap_or("");
+    match last_attr.split_once('=') {
+        Some((key, val)) => {
+            key.trim().eq_ignore_ascii_case("expires")
+                && !val.trim().is_empty()
+                && val.trim().chars().all(|c| c.is_ascii_alphabetic())
+        }
+        None => false,
+    }
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path.as_bytes()[cookie_path.len()] == b'/'))
+}
+
+fn default_path(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(i) => &path[..i],
+    }
+}
+
+// Parses the date formats allowed in `Expires`, e.g.
+// `Wed, 21 Oct 2015 07:28:00 GMT` and `Wednesday, 21-Oct-15 07:28:00 GMT`.
+fn parse_http_date(s: &str) -> Option<SystemTime> {
+    let s = s.split_once(',').map(|(_, date)| date).unwrap_or(s);
+    let mut fields = s.split([' ', '-']).filter(|f| !f.is_empty());
+    let day: u64 = fields.next()?.parse().ok()?;
+    let month = match fields.next()?.to_ascii_lowercase().as_str() {
+        "jan" => 1,
+        "feb" => 2,
+        "mar" => 3,
+        "apr" => 4,
+        "may" => 5,
+        "jun" => 6,
+        "jul" => 7,
+        "aug" => 8,
+        "sep" => 9,
+        "oct" => 10,
+        "nov" => 11,
+        "dec" => 12,
+        _ => return None,
+    };
+    let mut year: i64 = fields.next()?.parse().ok()?;
+    if year < 70 {
+        year += 2000;
+    } else if year < 100 {
+        year += 1900;
+    }
+    let mut time = fields.next()?.split(':').map(|f| f.parse::<u64>().ok());
+    let (hour, min, sec) = (time.next()??, time.next()??, time.next()??);
+    // RFC 6265 rejects years before 1601; later than 9999 is not a date
+    if !(1601..=9999).contains(&year)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || min > 59
+        || sec > 60
+    {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    if days < 0 {
+        return Some(UNIX_EPOCH);
+    }
+    let secs = days as u64 * 86400 + hour * 3600 + min * 60 + sec;
+    UNIX_EPOCH.checked_add(Duration::from_secs(secs))
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar. `year` must
+// be within 1601..=9999.
+fn days_from_civil(year: i64, month: u64, day: u64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
@@ -0,0 +1,57 @@
+use std::{fmt::Display, process::ExitCode};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    env::Env,
+    input,
+    output::{self, Output, EXIT_ENV_ERROR, EXIT_INPUT_ERROR},
+    read_stdin_to_end, BlocklessError, InputErrorKind,
+};
+
+pub fn run<T, O, E, F>(handler: F) -> ExitCode
+where
+    T: DeserializeOwned,
+    O: Serialize,
+    E: Display,
+    F: FnOnce(T, Env) -> Result<O, E>,
+{
+    run_with(input::Format::Auto, output::Format::Json, handler)
+}
+
+// Reads and decodes stdin, loads the environment, calls `handler` and writes
+// its result as an output envelope. Exits with `EXIT_INPUT_ERROR` when the
+// input cannot be decoded, `EXIT_ENV_ERROR` when the environment cannot be
+// read and `EXIT_FAILURE` when the handler fails.
+pub fn run_with<T, O, E, F>(
+    input_format: input::Format,
+    output_format: output::Format,
+    handler: F,
+) -> ExitCode
+where
+    T: DeserializeOwned,
+    O: Serialize,
+    E: Display,
+    F: FnOnce(T, Env) -> Result<O, E>,
+{
+    let output = Output::new(output_format);
+    let input = match read_input(input_format) {
+        Ok(input) => input,
+        Err(e) => return output.fail(BlocklessError::from(e), EXIT_INPUT_ERROR),
+    };
+    let env = match Env::load() {
+        Ok(env) => env,
+        Err(e) => return output.fail(BlocklessError::from(e), EXIT_ENV_ERROR),
+    };
+    output.finish(handler(input, env))
+}
+
+// An empty input decodes as `null`, so handlers taking `()` or an `Option`
+// can be invoked without any input.
+fn read_input<T: DeserializeOwned>(format: input::Format) -> Result<T, InputErrorKind> {
+    let bytes = read_stdin_to_end().map_err(InputErrorKind::ReadError)?;
+    if bytes.iter().all(u8::is_ascii_whitespace) {
+        return serde_json::from_slice(b"null").map_err(|_| InputErrorKind::Empty);
+    }
+    input::decode(&bytes, format)
+}
@@ -0,0 +1,173 @@
+use std::{collections::BTreeMap, fmt::Display, io, str::FromStr, sync::OnceLock};
+
+use json::JsonValue;
+
+use crate::{memory::read_whole, read_env_vars, EnvErrorKind, Secret, Zeroize};
+
+const INITIAL_PAYLOAD_SIZE: usize = 4096;
+
+pub const MAX_PAYLOAD_SIZE: usize = 4 * 1024 * 1024;
+
+static ENV: OnceLock<Env> = OnceLock::new();
+
+// The environment variables passed to the function (`--env` / `bls function
+// invoke --env`), parsed from the `env_var_read` host payload. Values are
+// held as secrets: they are redacted in `Debug` and wiped on drop.
+#[derive(Clone, Debug, Default)]
+pub struct Env {
+    vars: BTreeMap<String, Secret<String>>,
+}
+
+impl Env {
+    pub fn load() -> Result<Self, EnvErrorKind> {
+        Self::load_from(read_env_vars)
+    }
+
+    // Like `load`, reading the payload with `read` instead of
+    // `env_var_read`. Fails with `TooLarge` when the payload doesn't fit
+    // in `MAX_PAYLOAD_SIZE` bytes.
+    pub fn load_from(read: impl FnMut(&mut [u8]) -> io::Result<u32>) -> Result<Self, EnvErrorKind> {
+        let mut payload = read_whole(INITIAL_PAYLOAD_SIZE, MAX_PAYLOAD_SIZE, read)
+            .map_err(EnvErrorKind::ReadError)?
+            .ok_or(EnvErrorKind::TooLarge)?;
+        let env = Self::parse(&payload);
+        payload.zeroize();
+        env
+    }
+
+    // The runtime hands the variables over as a JSON object, possibly padded
+    // with NUL bytes. NUL or newline separated `NAME=value` pairs are accepted
+    // as well. The values parsed from JSON pass through a `JsonValue`, which
+    // is dropped without being wiped.
+    pub fn parse(payload: &[u8]) -> Result<Self, EnvErrorKind> {
+        let payload = std::str::from_utf8(payload).map_err(|_| EnvErrorKind::InvalidPayload)?;
+        let mut vars = BTreeMap::new();
+        for segment in payload.split('\0') {
+            if segment.trim().is_empty() {
+                continue;
+            }
+            if segment.trim_start().starts_with('{') {
+                let json = json::parse(segment).map_err(|_| EnvErrorKind::InvalidPayload)?;
+                for (name, value) in json.entries() {
+                    let value = match value {
+                        JsonValue::String(_) | JsonValue::Short(_) => {
+                            value.as_str().unwrap_or_default().to_string()
+                        }
+                        _ => value.dump(),
+                    };
+                    vars.insert(name.to_string(), Secret::new(value));
+                }
+                continue;
+            }
+            // only the name is trimmed, spaces in values are kept
+            for line in segment.lines().filter(|l| !l.trim().is_empty()) {
+                let (name, value) = line.split_once('=').ok_or(EnvErrorKind::InvalidPayload)?;
+                vars.insert(name.trim().to_string(), Secret::from(value));
+            }
+        }
+        Ok(Env { vars })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars
+            .get(name)
+            .map(|value| value.expose_secret().as_str())
+    }
+
+    pub fn secret(&self, name: &str) -> Option<Secret<String>> {
+        self.vars.get(name).cloned()
+    }
+
+    pub fn require_secret(&self, name: &str) -> Result<Secret<String>, EnvErrorKind> {
+        self.secret(name)
+            .ok_or_else(|| EnvErrorKind::Missing(name.to_string()))
+    }
+
+    pub fn require(&self, name: &str) -> Result<&str, EnvErrorKind> {
+        self.get(name)
+            .ok_or_else(|| EnvErrorKind::Missing(name.to_string()))
+    }
+
+    pub fn get_parse<T>(&self, name: &str) -> Result<Option<T>, EnvErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(name)
+            .map(|value| parse_value(name, value))
+            .transpose()
+    }
+
+    pub fn require_parse<T>(&self, name: &str) -> Result<T, EnvErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        parse_value(name, self.require(name)?)
+    }
+
+    pub fn get_parse_or<T>(&self, name: &str, default: T) -> Result<T, EnvErrorKind>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.get_parse(name)?.unwrap_or(default))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.vars
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.expose_secret().as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.vars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+}
+
+// The process wide environment, read from the host on first use.
+pub fn load() -> Result<&'static Env, EnvErrorKind> {
+    if let Some(env) = ENV.get() {
+        return Ok(env);
+    }
+    let env = Env::load()?;
+    Ok(ENV.get_or_init(|| env))
+}
+
+pub fn var(name: &str) -> Result<&'static str, EnvErrorKind> {
+    load()?.require(name)
+}
+
+pub fn var_opt(name: &str) -> Result<Option<&'static str>, EnvErrorKind> {
+    Ok(load()?.get(name))
+}
+
+pub fn var_parse<T>(name: &str) -> Result<T, EnvErrorKind>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    load()?.require_parse(name)
+}
+
+pub fn vars() -> Result<impl Iterator<Item = (&'static str, &'static str)>, EnvErrorKind> {
+    Ok(load()?.iter())
+}
+
+fn parse_value<T>(name: &str, value: &str) -> Result<T, EnvErrorKind>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e: T::Err| EnvErrorKind::Malformed {
+            name: name.to_string(),
+            reason: e.to_string(),
+        })
+}
@@ -1,3 +1,76 @@
+use crate::permissions::Permission;
+
+// Maps the error codes a host module returns to an error kind and back. The
+// first block is the module's ABI: each code the runtime returns, in the
+// order the runtime defines them. The `sdk` block gives errors raised by the
+// SDK itself codes from the reserved range starting at 1000, so that every
+// kind converts into a `u32`. The `local` block maps SDK errors that stand
+// in for a host error, like `MissingPermission`, to the host's code. The
+// `carried` block is for variants that carry the host's code along with
+// the operation that failed. Any other code becomes `Unknown(code)`.
+macro_rules! host_error_codes {
+    (
+        $kind:ident {
+            $($code:literal => $variant:ident,)*
+        }
+        $(sdk {
+            $($sdk_code:literal => $sdk_variant:ident,)*
+        })?
+        $(local {
+            $($local_pat:pat => $local_code:expr,)*
+        })?
+        $(carried {
+            $($carried_pat:pat => $carried_code:expr,)*
+        })?
+    ) => {
+        impl $kind {
+            // The ABI of the host module as `(code, variant name)` pairs.
+            pub const HOST_CODES: &'static [(u32, &'static str)] =
+                &[$(($code, stringify!($variant)),)*];
+
+            // The code the host returned for this error, `None` for errors
+            // raised by the SDK itself.
+            pub fn host_code(&self) -> Option<u32> {
+                match self {
+                    $(Self::$variant => Some($code),)*
+                    $($(Self::$sdk_variant => None,)*)?
+                    $($($local_pat => None,)*)?
+                    $($($carried_pat => Some($carried_code),)*)?
+                    Self::Unknown(code) => Some(*code),
+                }
+            }
+        }
+
+        impl From<u32> for $kind {
+            fn from(i: u32) -> $kind {
+                match i {
+                    $($code => $kind::$variant,)*
+                    $($($sdk_code => $kind::$sdk_variant,)*)?
+                    code => $kind::Unknown(code),
+                }
+            }
+        }
+
+        impl From<&$kind> for u32 {
+            fn from(e: &$kind) -> u32 {
+                match e {
+                    $($kind::$variant => $code,)*
+                    $($($kind::$sdk_variant => $sdk_code,)*)?
+                    $($($local_pat => $local_code,)*)?
+                    $($($carried_pat => $carried_code,)*)?
+                    $kind::Unknown(code) => *code,
+                }
+            }
+        }
+
+        impl From<$kind> for u32 {
+            fn from(e: $kind) -> u32 {
+                u32::from(&e)
+            }
+        }
+    };
+}
+
 #[derive(Debug)]
 pub enum HttpErrorKind {
     InvalidDriver,
@@ -14,10 +87,40 @@ pub enum HttpErrorKind {
     RuntimeError,
     TooManySessions,
     PermissionDeny,
+    BodyTooLarge,
+    ReadTimeout,
+    MissingPermission(Permission),
+    Unknown(u32),
 }
 
 impl std::error::Error for HttpErrorKind {}
 
+host_error_codes! {
+    HttpErrorKind {
+        1 => InvalidHandle,
+        2 => MemoryAccessError,
+        3 => BufferTooSmall,
+        4 => HeaderNotFound,
+        5 => Utf8Error,
+        6 => DestinationNotAllowed,
+        7 => InvalidMethod,
+        8 => InvalidEncoding,
+        9 => InvalidUrl,
+        10 => RequestError,
+        11 => RuntimeError,
+        12 => TooManySessions,
+        13 => PermissionDeny,
+    }
+    sdk {
+        1000 => InvalidDriver,
+        1001 => BodyTooLarge,
+        1002 => ReadTimeout,
+    }
+    local {
+        HttpErrorKind::MissingPermission(_) => 13,
+    }
+}
+
 impl std::fmt::Display for HttpErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
@@ -35,27 +138,12 @@ impl std::fmt::Display for HttpErrorKind {
             Self::RuntimeError => write!(f, "Runtime error"),
             Self::TooManySessions => write!(f, "Too many sessions"),
             Self::PermissionDeny => write!(f, "Permision deny."),
-        }
-    }
-}
-
-impl From<u32> for HttpErrorKind {
-    fn from(i: u32) -> HttpErrorKind {
-        match i {
-            1 => HttpErrorKind::InvalidHandle,
-            2 => HttpErrorKind::MemoryAccessError,
-            3 => HttpErrorKind::BufferTooSmall,
-            4 => HttpErrorKind::HeaderNotFound,
-            5 => HttpErrorKind::Utf8Error,
-            6 => HttpErrorKind::DestinationNotAllowed,
-            7 => HttpErrorKind::InvalidMethod,
-            8 => HttpErrorKind::InvalidEncoding,
-            9 => HttpErrorKind::InvalidUrl,
-            10 => HttpErrorKind::RequestError,
-            11 => HttpErrorKind::RuntimeError,
-            12 => HttpErrorKind::TooManySessions,
-            13 => HttpErrorKind::PermissionDeny,
-            _ => HttpErrorKind::RuntimeError,
+            Self::BodyTooLarge => write!(f, "Body too large"),
+            Self::ReadTimeout => write!(f, "Body read deadline exceeded"),
+            Self::MissingPermission(ref permission) => {
+                write!(f, "Missing permission {}", permission)
+            }
+            Self::Unknown(code) => write!(f, "Unknown error code {}", code),
         }
     }
 }
@@ -66,6 +154,7 @@ pub enum SocketErrorKind {
     ParameterError,
     ConnectionReset,
     AddressInUse,
+    Unknown(u32),
 }
 
 impl std::fmt::Display for SocketErrorKind {
@@ -75,37 +164,74 @@ impl std::fmt::Display for SocketErrorKind {
             SocketErrorKind::ParameterError => write!(f, "Parameter Error."),
             SocketErrorKind::ConnectionReset => write!(f, "Connection  Reset."),
             SocketErrorKind::AddressInUse => write!(f, "Address In Use."),
+            SocketErrorKind::Unknown(code) => write!(f, "Unknown error code {}.", code),
         }
     }
 }
 
 impl std::error::Error for SocketErrorKind {}
 
+host_error_codes! {
+    SocketErrorKind {
+        1 => ConnectRefused,
+        2 => ParameterError,
+        3 => ConnectionReset,
+        4 => AddressInUse,
+    }
+}
+
 #[derive(Debug)]
 pub enum CGIErrorKind {
-    ListError,
+    // The failed host calls, with the code the host returned.
+    ListError(u32),
+    ExecError(u32),
+    ReadError(u32),
     EncodingError,
     JsonDecodingError,
-    ExecError,
-    ReadError,
     NoCommandError,
+    MissingPermission(Permission),
+    Unknown(u32),
 }
 
 impl std::fmt::Display for CGIErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            CGIErrorKind::ListError => write!(f, "CGI List Error."),
+            CGIErrorKind::ListError(code) => write!(f, "CGI List Error (code {}).", code),
             CGIErrorKind::EncodingError => write!(f, "CGI Encoding Error."),
             CGIErrorKind::JsonDecodingError => write!(f, "Json decoding Error."),
-            CGIErrorKind::ExecError => write!(f, "CGI Exec Error."),
-            CGIErrorKind::ReadError => write!(f, "Read Error."),
+            CGIErrorKind::ExecError(code) => write!(f, "CGI Exec Error (code {}).", code),
+            CGIErrorKind::ReadError(code) => write!(f, "Read Error (code {}).", code),
             CGIErrorKind::NoCommandError => write!(f, "No CGI Command Error."),
+            CGIErrorKind::MissingPermission(ref permission) => {
+                write!(f, "Missing permission {}.", permission)
+            }
+            CGIErrorKind::Unknown(code) => write!(f, "Unknown error code {}.", code),
         }
     }
 }
 
 impl std::error::Error for CGIErrorKind {}
 
+// The cgi module doesn't publish its codes, so the host's code is carried by
+// the variant of the failed call, and `From<u32>` keeps it as
+// `Unknown(code)`.
+host_error_codes! {
+    CGIErrorKind {}
+    sdk {
+        1000 => EncodingError,
+        1001 => JsonDecodingError,
+        1002 => NoCommandError,
+    }
+    local {
+        CGIErrorKind::MissingPermission(_) => 1003,
+    }
+    carried {
+        CGIErrorKind::ListError(code) => *code,
+        CGIErrorKind::ExecError(code) => *code,
+        CGIErrorKind::ReadError(code) => *code,
+    }
+}
+
 #[derive(Debug)]
 pub enum IPFSErrorKind {
     Success,
@@ -119,10 +245,49 @@ pub enum IPFSErrorKind {
     TooManySessions,
     InvalidDriver,
     PermissionDeny,
+    MissingPermission(Permission),
+    // A non-200 response of the IPFS api, with its error message.
+    ApiError {
+        status: u32,
+        code: Option<i64>,
+        message: String,
+    },
+    // A dag node that could not be encoded or decoded.
+    Ipld(IpldErrorKind),
+    Unknown(u32),
 }
 
+// `Ipld` prints its inner error, like `ApiError` its message, so it has no
+// `source`.
 impl std::error::Error for IPFSErrorKind {}
 
+impl From<IpldErrorKind> for IPFSErrorKind {
+    fn from(e: IpldErrorKind) -> IPFSErrorKind {
+        IPFSErrorKind::Ipld(e)
+    }
+}
+
+host_error_codes! {
+    IPFSErrorKind {
+        0 => Success,
+        1 => InvalidHandle,
+        2 => Utf8Error,
+        3 => InvalidMethod,
+        4 => InvalidParameter,
+        5 => InvalidEncoding,
+        6 => RequestError,
+        7 => RuntimeError,
+        8 => TooManySessions,
+        9 => InvalidDriver,
+        10 => PermissionDeny,
+    }
+    local {
+        IPFSErrorKind::MissingPermission(_) => 10,
+        IPFSErrorKind::ApiError { .. } => 6,
+        IPFSErrorKind::Ipld(_) => 4,
+    }
+}
+
 impl std::fmt::Display for IPFSErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
@@ -137,25 +302,22 @@ impl std::fmt::Display for IPFSErrorKind {
             Self::TooManySessions => write!(f, "Too many sessions"),
             Self::InvalidDriver => write!(f, "Invalid Driver"),
             Self::PermissionDeny => write!(f, "Permission Deny"),
-        }
-    }
-}
-
-impl From<u32> for IPFSErrorKind {
-    fn from(i: u32) -> IPFSErrorKind {
-        match i {
-            0 => IPFSErrorKind::Success,
-            1 => IPFSErrorKind::InvalidHandle,
-            2 => IPFSErrorKind::Utf8Error,
-            3 => IPFSErrorKind::InvalidMethod,
-            4 => IPFSErrorKind::InvalidParameter,
-            5 => IPFSErrorKind::InvalidEncoding,
-            6 => IPFSErrorKind::RequestError,
-            7 => IPFSErrorKind::RuntimeError,
-            8 => IPFSErrorKind::TooManySessions,
-            9 => IPFSErrorKind::InvalidDriver,
-            10 => IPFSErrorKind::PermissionDeny,
-            _ => IPFSErrorKind::RuntimeError,
+            Self::MissingPermission(ref permission) => {
+                write!(f, "Missing permission {}", permission)
+            }
+            Self::ApiError {
+                status,
+                code,
+                ref message,
+            } => {
+                write!(f, "IPFS api error (status {}", status)?;
+                if let Some(code) = code {
+                    write!(f, ", code {}", code)?;
+                }
+                write!(f, "): {}", message)
+            }
+            Self::Ipld(ref e) => write!(f, "IPLD error: {}", e),
+            Self::Unknown(code) => write!(f, "Unknown error code {}", code),
         }
     }
 }
@@ -175,6 +337,8 @@ pub enum AWSS3ErrorKind {
     TooManySessions,
     InvalidDriver,
     PermissionDeny,
+    MissingPermission(Permission),
+    Unknown(u32),
 }
 
 impl std::fmt::Display for AWSS3ErrorKind {
@@ -193,28 +357,379 @@ impl std::fmt::Display for AWSS3ErrorKind {
             Self::TooManySessions => write!(f, "Too many sessions"),
             Self::InvalidDriver => write!(f, "Invalid Driver"),
             Self::PermissionDeny => write!(f, "Permission Deny"),
+            Self::MissingPermission(ref permission) => {
+                write!(f, "Missing permission {}", permission)
+            }
+            Self::Unknown(code) => write!(f, "Unknown error code {}", code),
+        }
+    }
+}
+
+impl std::error::Error for AWSS3ErrorKind {}
+
+host_error_codes! {
+    AWSS3ErrorKind {
+        0 => Success,
+        1 => InvalidHandle,
+        2 => Utf8Error,
+        3 => InvalidMethod,
+        4 => InvalidParameter,
+        5 => InvalidEncoding,
+        6 => CredentialsError,
+        7 => RegionError,
+        8 => RequestError,
+        9 => RuntimeError,
+        10 => TooManySessions,
+        11 => InvalidDriver,
+        12 => PermissionDeny,
+    }
+    local {
+        AWSS3ErrorKind::MissingPermission(_) => 12,
+    }
+}
+
+#[derive(Debug)]
+pub enum OAuth2ErrorKind {
+    HttpError(HttpErrorKind),
+    Utf8Error,
+    InvalidResponse,
+    MissingRefreshToken,
+    TokenRequestFailed {
+        status: u32,
+        error: Option<String>,
+        description: Option<String>,
+    },
+}
+
+impl std::fmt::Display for OAuth2ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HttpError(_) => write!(f, "Token request http error"),
+            Self::Utf8Error => write!(f, "UTF-8 Error"),
+            Self::InvalidResponse => write!(f, "Invalid token response"),
+            Self::MissingRefreshToken => write!(f, "No refresh token available"),
+            Self::TokenRequestFailed {
+                status,
+                error,
+                description,
+            } => {
+                write!(f, "Token request failed with status {}", status)?;
+                if let Some(error) = error {
+                    write!(f, ": {}", error)?;
+                }
+                if let Some(description) = description {
+                    write!(f, " ({})", description)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
-impl From<u32> for AWSS3ErrorKind {
-    fn from(i: u32) -> AWSS3ErrorKind {
-        match i {
-            0 => AWSS3ErrorKind::Success,
-            1 => AWSS3ErrorKind::InvalidHandle,
-            2 => AWSS3ErrorKind::Utf8Error,
-            3 => AWSS3ErrorKind::InvalidMethod,
-            4 => AWSS3ErrorKind::InvalidParameter,
-            5 => AWSS3ErrorKind::InvalidEncoding,
-            6 => AWSS3ErrorKind::CredentialsError,
-            7 => AWSS3ErrorKind::RegionError,
-            8 => AWSS3ErrorKind::RequestError,
-            9 => AWSS3ErrorKind::TooManySessions,
-            10 => AWSS3ErrorKind::InvalidDriver,
-            11 => AWSS3ErrorKind::PermissionDeny,
-            _ => AWSS3ErrorKind::RuntimeError,
+impl std::error::Error for OAuth2ErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::HttpError(e) => Some(e),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for AWSS3ErrorKind {}
+impl From<HttpErrorKind> for OAuth2ErrorKind {
+    fn from(e: HttpErrorKind) -> OAuth2ErrorKind {
+        OAuth2ErrorKind::HttpError(e)
+    }
+}
+
+#[derive(Debug)]
+pub enum EnvErrorKind {
+    ReadError(std::io::Error),
+    InvalidPayload,
+    TooLarge,
+    Missing(String),
+    Malformed { name: String, reason: String },
+}
+
+impl std::fmt::Display for EnvErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadError(_) => write!(f, "Read environment error"),
+            Self::InvalidPayload => write!(f, "Invalid environment payload"),
+            Self::TooLarge => write!(f, "Environment payload too large"),
+            Self::Missing(name) => write!(f, "Environment variable {} is not set", name),
+            Self::Malformed { name, reason } => {
+                write!(f, "Environment variable {} is malformed: {}", name, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ReadError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputErrorKind {
+    ReadError(std::io::Error),
+    Empty,
+    UnsupportedFormat(&'static str),
+    Decode {
+        format: &'static str,
+        offset: Option<usize>,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for InputErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadError(_) => write!(f, "Read input error"),
+            Self::Empty => write!(f, "Input is empty"),
+            Self::UnsupportedFormat(format) => {
+                write!(f, "Input format {} is not enabled", format)
+            }
+            Self::Decode {
+                format,
+                offset: Some(offset),
+                message,
+            } => write!(
+                f,
+                "Invalid {} input at byte {}: {}",
+                format, offset, message
+            ),
+            Self::Decode {
+                format, message, ..
+            } => write!(f, "Invalid {} input: {}", format, message),
+        }
+    }
+}
+
+impl std::error::Error for InputErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ReadError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CidErrorKind {
+    InvalidMultibase,
+    UnsupportedMultibase(char),
+    InvalidVarint,
+    UnsupportedVersion(u64),
+    InvalidMultihash,
+    InvalidV0,
+    InvalidLength,
+}
+
+impl std::fmt::Display for CidErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidMultibase => write!(f, "Invalid multibase encoding"),
+            Self::UnsupportedMultibase(prefix) => {
+                write!(f, "Unsupported multibase prefix {}", prefix)
+            }
+            Self::InvalidVarint => write!(f, "Invalid varint"),
+            Self::UnsupportedVersion(version) => write!(f, "Unsupported CID version {}", version),
+            Self::InvalidMultihash => write!(f, "Invalid multihash"),
+            Self::InvalidV0 => write!(f, "CIDv0 must be a dag-pb sha2-256 hash in base58btc"),
+            Self::InvalidLength => write!(f, "Invalid CID length"),
+        }
+    }
+}
+
+impl std::error::Error for CidErrorKind {}
+
+#[derive(Debug)]
+pub enum IpldErrorKind {
+    InvalidDagJson(String),
+    InvalidDagCbor(String),
+    InvalidLink(CidErrorKind),
+    // a value the codec can't represent, e.g. a NaN float
+    UnsupportedValue(String),
+    Serde(String),
+}
+
+impl std::fmt::Display for IpldErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidDagJson(e) => write!(f, "Invalid dag-json: {}", e),
+            Self::InvalidDagCbor(e) => write!(f, "Invalid dag-cbor: {}", e),
+            Self::InvalidLink(e) => write!(f, "Invalid link: {}", e),
+            Self::UnsupportedValue(e) => write!(f, "Unsupported value: {}", e),
+            Self::Serde(e) => write!(f, "Serde error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IpldErrorKind {}
+
+/// One error type for code that uses several host modules. Every module error
+/// converts into it with `?`, and `context` wraps it with a description of
+/// the failed operation.
+///
+/// `Display` shows one level of the chain and `source()` returns the next, so
+/// reporters that walk `source()` print each cause once. `{:#}` prints the
+/// whole chain on one line.
+///
+/// ```
+/// use blockless_sdk::*;
+///
+/// fn root(s: &str) -> Result<Cid, BlocklessError> {
+///     s.parse::<Cid>().with_context(|| format!("parse root {}", s))
+/// }
+///
+/// let err = root("not-a-cid").unwrap_err();
+/// assert_eq!(err.to_string(), "parse root not-a-cid");
+/// assert!(format!("{:#}", err).starts_with("parse root not-a-cid: CID error: "));
+///
+/// let err = BlocklessError::from(EnvErrorKind::ReadError(std::io::ErrorKind::NotFound.into()))
+///     .context("load env");
+/// assert_eq!(
+///     format!("{:#}", err),
+///     "load env: Env error: Read environment error: entity not found"
+/// );
+/// ```
+#[derive(Debug)]
+pub enum BlocklessError {
+    Http(HttpErrorKind),
+    Ipfs(IPFSErrorKind),
+    S3(AWSS3ErrorKind),
+    Cgi(CGIErrorKind),
+    Socket(SocketErrorKind),
+    OAuth2(OAuth2ErrorKind),
+    Env(EnvErrorKind),
+    Input(InputErrorKind),
+    Cid(CidErrorKind),
+    Ipld(IpldErrorKind),
+    Context {
+        context: String,
+        source: Box<BlocklessError>,
+    },
+}
+
+impl BlocklessError {
+    pub fn context(self, context: impl Into<String>) -> BlocklessError {
+        BlocklessError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    // The error without any context around it.
+    pub fn root(&self) -> &BlocklessError {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    // The raw code returned by the host call that failed, if the error came
+    // from the host.
+    pub fn host_code(&self) -> Option<u32> {
+        match self.root() {
+            Self::Http(e) => e.host_code(),
+            Self::Ipfs(e) => e.host_code(),
+            Self::S3(e) => e.host_code(),
+            Self::Cgi(e) => e.host_code(),
+            Self::Socket(e) => e.host_code(),
+            Self::OAuth2(OAuth2ErrorKind::HttpError(e)) => e.host_code(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BlocklessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "Http error: {}", e),
+            Self::Ipfs(e) => write!(f, "IPFS error: {}", e),
+            Self::S3(e) => write!(f, "S3 error: {}", e),
+            Self::Cgi(e) => write!(f, "CGI error: {}", e),
+            Self::Socket(e) => write!(f, "Socket error: {}", e),
+            Self::OAuth2(e) => write!(f, "OAuth2 error: {}", e),
+            Self::Env(e) => write!(f, "Env error: {}", e),
+            Self::Input(e) => write!(f, "Input error: {}", e),
+            Self::Cid(e) => write!(f, "CID error: {}", e),
+            Self::Ipld(e) => write!(f, "IPLD error: {}", e),
+            Self::Context { context, .. } => write!(f, "{}", context),
+        }?;
+        if f.alternate() {
+            let mut source = std::error::Error::source(self);
+            while let Some(e) = source {
+                write!(f, ": {}", e)?;
+                source = e.source();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BlocklessError {
+    // A module error is already part of `Display`, so its own source comes
+    // next.
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(e) => e.source(),
+            Self::Ipfs(e) => e.source(),
+            Self::S3(e) => e.source(),
+            Self::Cgi(e) => e.source(),
+            Self::Socket(e) => e.source(),
+            Self::OAuth2(e) => e.source(),
+            Self::Env(e) => e.source(),
+            Self::Input(e) => e.source(),
+            Self::Cid(e) => e.source(),
+            Self::Ipld(e) => e.source(),
+            Self::Context { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+macro_rules! impl_from_kind {
+    ($($kind:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$kind> for BlocklessError {
+                fn from(e: $kind) -> BlocklessError {
+                    BlocklessError::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_kind! {
+    HttpErrorKind => Http,
+    IPFSErrorKind => Ipfs,
+    AWSS3ErrorKind => S3,
+    CGIErrorKind => Cgi,
+    SocketErrorKind => Socket,
+    OAuth2ErrorKind => OAuth2,
+    EnvErrorKind => Env,
+    InputErrorKind => Input,
+    CidErrorKind => Cid,
+    IpldErrorKind => Ipld,
+}
+
+// Adds context to any result whose error converts into `BlocklessError`.
+pub trait ResultExt<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, BlocklessError>;
+
+    fn with_context<C: Into<String>>(self, f: impl FnOnce() -> C) -> Result<T, BlocklessError>;
+}
+
+impl<T, E: Into<BlocklessError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, BlocklessError> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, f: impl FnOnce() -> C) -> Result<T, BlocklessError> {
+        self.map_err(|e| e.into().context(f()))
+    }
+}
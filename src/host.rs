@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static HOST_CALLS: AtomicU64 = AtomicU64::new(0);
+
+// Number of calls made into the blockless host modules so far.
+pub fn host_call_count() -> u64 {
+    HOST_CALLS.load(Ordering::Relaxed)
+}
+
+pub(crate) fn record_host_call() {
+    HOST_CALLS.fetch_add(1, Ordering::Relaxed);
+}
+
+// Declares the imports of a host module. Each import is wrapped by a
+// `pub(crate) unsafe fn` of the same name that counts the call.
+macro_rules! host_imports {
+    (
+        #[link(wasm_import_module = $module:literal)]
+        extern "C" {
+            $(
+                #[link_name = $link_name:literal]
+                $(#[$attr:meta])*
+                pub(crate) fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty;
+            )*
+        }
+    ) => {
+        mod ffi {
+            #[link(wasm_import_module = $module)]
+            extern "C" {
+                $(
+                    #[link_name = $link_name]
+                    pub(super) fn $name($($arg: $ty),*) -> $ret;
+                )*
+            }
+        }
+
+        $(
+            $(#[$attr])*
+            #[inline]
+            pub(crate) unsafe fn $name($($arg: $ty),*) -> $ret {
+                crate::host::record_host_call();
+                ffi::$name($($arg),*)
+            }
+        )*
+    };
+}
+
+pub(crate) use host_imports;
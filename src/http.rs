@@ -1,6 +1,14 @@
-use std::cmp::Ordering;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
 
-use crate::{error::HttpErrorKind, http_host::*};
+use crate::{
+    error::HttpErrorKind,
+    http_host::*,
+    permissions::{self, Permission},
+    ReadLoop,
+};
 use json::JsonValue;
 
 pub type Handle = u32;
@@ -10,13 +18,19 @@ pub type CodeStatus = u32;
 pub struct BlocklessHttp {
     inner: Handle,
     code: CodeStatus,
+    max_body_size: Option<u64>,
+    max_read_time: Option<Duration>,
 }
 
+#[derive(Clone)]
 pub struct HttpOptions {
     pub method: String,
     pub connect_timeout: u32,
     pub read_timeout: u32,
     pub body: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    pub max_body_size: Option<u64>,
+    pub max_read_time: Option<Duration>,
 }
 
 impl HttpOptions {
@@ -26,15 +40,36 @@ impl HttpOptions {
             connect_timeout,
             read_timeout,
             body: None,
+            headers: BTreeMap::new(),
+            max_body_size: None,
+            max_read_time: None,
         }
     }
 
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.insert(name.into(), value.into());
+    }
+
+    // Limits enforced by the SDK while reading the response body, they are not
+    // sent to the host.
+    pub fn set_max_body_size(&mut self, max_body_size: u64) {
+        self.max_body_size = Some(max_body_size);
+    }
+
+    pub fn set_max_read_time(&mut self, max_read_time: Duration) {
+        self.max_read_time = Some(max_read_time);
+    }
+
     pub fn dump(&self) -> String {
         let mut json = JsonValue::new_object();
         json["method"] = self.method.clone().into();
         json["connectTimeout"] = self.connect_timeout.into();
         json["readTimeout"] = self.read_timeout.into();
-        json["headers"] = "{}".into();
+        let mut headers = JsonValue::new_object();
+        for (name, value) in &self.headers {
+            headers[name.as_str()] = value.clone().into();
+        }
+        json["headers"] = headers.dump().into();
         json["body"] = self.body.clone().into();
         json.dump()
     }
@@ -42,6 +77,10 @@ impl HttpOptions {
 
 impl BlocklessHttp {
     pub fn open(url: &str, opts: &HttpOptions) -> Result<Self, HttpErrorKind> {
+        permissions::require(Permission::Url(url.to_string()))
+            .map_err(HttpErrorKind::MissingPermission)?;
+        let max_body_size = opts.max_body_size;
+        let max_read_time = opts.max_read_time;
         let opts = opts.dump();
         let mut fd = 0;
         let mut status = 0;
@@ -61,6 +100,8 @@ impl BlocklessHttp {
         Ok(Self {
             inner: fd,
             code: status,
+            max_body_size,
+            max_read_time,
         })
     }
 
@@ -68,29 +109,55 @@ impl BlocklessHttp {
         self.code
     }
 
+    pub fn content_length(&self) -> Result<Option<u64>, HttpErrorKind> {
+        match self.get_header("Content-Length") {
+            Ok(len) => Ok(len.trim().parse().ok()),
+            Err(HttpErrorKind::HeaderNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn get_all_body(&self) -> Result<Vec<u8>, HttpErrorKind> {
         let mut vec = Vec::new();
-        loop {
-            let mut buf = [0u8; 1024];
-            let mut num: u32 = 0;
-            let rs =
-                unsafe { http_read_body(self.inner, buf.as_mut_ptr(), buf.len() as _, &mut num) };
-            if rs != 0 {
-                return Err(HttpErrorKind::from(rs));
-            }
+        self.read_all_body_into(&ReadLoop::new(), &mut vec)?;
+        Ok(vec)
+    }
 
-            match num.cmp(&0) {
-                Ordering::Greater => vec.extend_from_slice(&buf[0..num as _]),
-                _ => break,
+    // Appends the remaining body to `buf`, pre-sizing it from `Content-Length`
+    // unless `read_loop` already carries a size hint.
+    pub fn read_all_body_into(
+        &self,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, HttpErrorKind> {
+        let content_length = self.content_length()?;
+        if let (Some(max), Some(len)) = (self.max_body_size, content_length) {
+            if len > max {
+                return Err(HttpErrorKind::BodyTooLarge);
             }
         }
-        Ok(vec)
+        let mut read_loop = read_loop.clone();
+        if let (None, Some(len)) = (read_loop.size_hint, content_length) {
+            read_loop.set_size_hint(len);
+        }
+        let deadline = self.max_read_time.map(|t| Instant::now() + t);
+        let mut total: u64 = 0;
+        read_loop.read_to_end(buf, |chunk| {
+            let num = self.read_body(chunk)?;
+            total += num as u64;
+            if matches!(self.max_body_size, Some(max) if total > max) {
+                return Err(HttpErrorKind::BodyTooLarge);
+            }
+            if matches!(deadline, Some(deadline) if Instant::now() > deadline) {
+                return Err(HttpErrorKind::ReadTimeout);
+            }
+            Ok(num as usize)
+        })
     }
 
     pub fn get_header(&self, header: &str) -> Result<String, HttpErrorKind> {
         let mut vec = Vec::new();
-        loop {
-            let mut buf = [0u8; 1024];
+        ReadLoop::new().read_to_end(&mut vec, |buf| {
             let mut num: u32 = 0;
             let rs = unsafe {
                 http_read_header(
@@ -105,11 +172,8 @@ impl BlocklessHttp {
             if rs != 0 {
                 return Err(HttpErrorKind::from(rs));
             }
-            match num.cmp(&0) {
-                Ordering::Greater => vec.extend_from_slice(&buf[0..num as _]),
-                _ => break,
-            }
-        }
+            Ok(num as usize)
+        })?;
         String::from_utf8(vec).map_err(|_| HttpErrorKind::Utf8Error)
     }
 
@@ -0,0 +1,90 @@
+use ::http::{HeaderName, HeaderValue, Request, Response, StatusCode};
+
+use crate::{BlocklessHttp, HttpErrorKind, HttpOptions};
+
+pub type Body = Vec<u8>;
+
+// The host can only look response headers up by name, these are the ones
+// copied into the `http::Response` by `send`.
+pub const DEFAULT_RESPONSE_HEADERS: &[&str] = &[
+    "cache-control",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "date",
+    "etag",
+    "last-modified",
+    "location",
+    "retry-after",
+    "set-cookie",
+    "www-authenticate",
+];
+
+// Sends the request through `BlocklessHttp`. Timeouts and read limits are
+// taken from an `HttpOptions` request extension when present; method,
+// headers and body always come from the request itself.
+pub fn send(req: Request<Vec<u8>>) -> Result<Response<Body>, HttpErrorKind> {
+    send_with_headers(req, DEFAULT_RESPONSE_HEADERS)
+}
+
+pub fn send_with_headers(
+    req: Request<Vec<u8>>,
+    response_headers: &[&str],
+) -> Result<Response<Body>, HttpErrorKind> {
+    let (parts, body) = req.into_parts();
+    if parts.uri.scheme().is_none() {
+        return Err(HttpErrorKind::InvalidUrl);
+    }
+    let mut opts = parts
+        .extensions
+        .get::<HttpOptions>()
+        .cloned()
+        .unwrap_or_else(|| HttpOptions::new("GET", 30, 10));
+    opts.method = parts.method.as_str().to_string();
+    opts.headers.clear();
+    for name in parts.headers.keys() {
+        let values = parts
+            .headers
+            .get_all(name)
+            .iter()
+            .map(|v| v.to_str().map_err(|_| HttpErrorKind::InvalidEncoding))
+            .collect::<Result<Vec<_>, _>>()?;
+        opts.set_header(name.as_str(), &values.join(", "));
+    }
+    opts.body = if body.is_empty() {
+        None
+    } else {
+        Some(String::from_utf8(body).map_err(|_| HttpErrorKind::Utf8Error)?)
+    };
+
+    let http = BlocklessHttp::open(&parts.uri.to_string(), &opts)?;
+    let response = read_response(&http, response_headers);
+    http.close();
+    response
+}
+
+fn read_response(
+    http: &BlocklessHttp,
+    response_headers: &[&str],
+) -> Result<Response<Body>, HttpErrorKind> {
+    let status = u16::try_from(http.get_code())
+        .ok()
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .ok_or(HttpErrorKind::RequestError)?;
+    let mut builder = Response::builder().status(status);
+    for name in response_headers {
+        let value = match http.get_header(name) {
+            Ok(value) => value,
+            Err(HttpErrorKind::HeaderNotFound) => continue,
+            Err(e) => return Err(e),
+        };
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            builder = builder.header(name, value);
+        }
+    }
+    let body = http.get_all_body()?;
+    builder.body(body).map_err(|_| HttpErrorKind::RuntimeError)
+}
@@ -0,0 +1,209 @@
+use serde::de::DeserializeOwned;
+
+use crate::{read_stdin_to_end, InputErrorKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Auto,
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Auto => "auto",
+            Format::Json => "json",
+            Format::Cbor => "cbor",
+            Format::MessagePack => "msgpack",
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Reads the whole function input and decodes it, detecting the format.
+pub fn read<T: DeserializeOwned>() -> Result<T, InputErrorKind> {
+    read_as(Format::Auto)
+}
+
+pub fn read_as<T: DeserializeOwned>(format: Format) -> Result<T, InputErrorKind> {
+    let bytes = read_stdin_to_end().map_err(InputErrorKind::ReadError)?;
+    decode(&bytes, format)
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8], format: Format) -> Result<T, InputErrorKind> {
+    match format {
+        Format::Json => decode_json(bytes),
+        Format::Cbor => decode_cbor(bytes),
+        Format::MessagePack => decode_msgpack(bytes),
+        Format::Auto => {
+            let mut error = None;
+            for format in detect(bytes)? {
+                match decode(bytes, format) {
+                    Ok(value) => return Ok(value),
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                }
+            }
+            if is_blank(bytes) {
+                return Err(InputErrorKind::Empty);
+            }
+            Err(error.unwrap_or(InputErrorKind::Empty))
+        }
+    }
+}
+
+// Orders the formats to try, the first error being the one reported. Input
+// that starts like JSON text is tried as JSON first, then as the enabled
+// binary formats: bytes such as `-`, digits, `f` or `{` also start valid CBOR
+// and MessagePack values, as does whitespace (0x20 is CBOR -1). Binary input
+// is tried in the order suggested by its first byte, e.g. 0x80..=0x9f is a
+// MessagePack map or array as well as a CBOR array.
+fn detect(bytes: &[u8]) -> Result<Vec<Format>, InputErrorKind> {
+    let &first = bytes.first().ok_or(InputErrorKind::Empty)?;
+    let json_start = bytes.starts_with(b"\xef\xbb\xbf")
+        || bytes
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .is_some_and(|b| {
+                matches!(
+                    b,
+                    b'{' | b'[' | b'"' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n'
+                )
+            });
+    // the CBOR self-describe tag
+    let binary = if bytes.starts_with(&[0xd9, 0xd9, 0xf7]) {
+        [Format::Cbor, Format::MessagePack]
+    } else {
+        match first {
+            0x80..=0x9f | 0xdc..=0xdf => [Format::MessagePack, Format::Cbor],
+            _ => [Format::Cbor, Format::MessagePack],
+        }
+    };
+    let mut formats = Vec::new();
+    if json_start {
+        formats.push(Format::Json);
+    }
+    formats.extend(binary.into_iter().filter(|f| BINARY_FORMATS.contains(f)));
+    if formats.is_empty() {
+        if is_blank(bytes) {
+            return Err(InputErrorKind::Empty);
+        }
+        return Err(InputErrorKind::UnsupportedFormat(binary[0].name()));
+    }
+    Ok(formats)
+}
+
+fn is_blank(bytes: &[u8]) -> bool {
+    bytes.iter().all(|b| b.is_ascii_whitespace())
+}
+
+// Binary decoders stop after one value; anything after it means the input
+// was not in that format.
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn trailing_bytes(format: Format, offset: usize) -> InputErrorKind {
+    InputErrorKind::Decode {
+        format: format.name(),
+        offset: Some(offset),
+        message: "trailing bytes after the value".to_string(),
+    }
+}
+
+const BINARY_FORMATS: &[Format] = &[
+    #[cfg(feature = "cbor")]
+    Format::Cbor,
+    #[cfg(feature = "msgpack")]
+    Format::MessagePack,
+];
+
+fn decode_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, InputErrorKind> {
+    let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    serde_json::from_slice(bytes).map_err(|e| InputErrorKind::Decode {
+        format: Format::Json.name(),
+        offset: json_offset(bytes, e.line(), e.column()),
+        message: e.to_string(),
+    })
+}
+
+// serde_json reports 1-based line and column, turn them into a byte offset.
+fn json_offset(bytes: &[u8], line: usize, column: usize) -> Option<usize> {
+    if line == 0 {
+        return None;
+    }
+    let line_start = if line == 1 {
+        0
+    } else {
+        bytes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .nth(line - 2)
+            .map(|(i, _)| i + 1)?
+    };
+    Some((line_start + column.saturating_sub(1)).min(bytes.len()))
+}
+
+#[cfg(feature = "cbor")]
+fn decode_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, InputErrorKind> {
+    use ciborium::de::Error;
+
+    let mut reader = bytes;
+    let value = ciborium::from_reader(&mut reader).map_err(|e| {
+        let (offset, message) = match e {
+            Error::Io(e) => (
+                Some(bytes.len()),
+                format!("unexpected end of input: {:?}", e),
+            ),
+            Error::Syntax(offset) => (Some(offset), "syntax error".to_string()),
+            Error::Semantic(offset, message) => (offset, message),
+            Error::RecursionLimitExceeded => (None, "recursion limit exceeded".to_string()),
+        };
+        InputErrorKind::Decode {
+            format: Format::Cbor.name(),
+            offset,
+            message,
+        }
+    })?;
+    if !reader.is_empty() {
+        return Err(trailing_bytes(Format::Cbor, bytes.len() - reader.len()));
+    }
+    Ok(value)
+}
+
+#[cfg(not(feature = "cbor"))]
+fn decode_cbor<T: DeserializeOwned>(_bytes: &[u8]) -> Result<T, InputErrorKind> {
+    Err(InputErrorKind::UnsupportedFormat(Format::Cbor.name()))
+}
+
+#[cfg(feature = "msgpack")]
+fn decode_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, InputErrorKind> {
+    // rmp-serde does not report positions, the offset is how far the
+    // decoder got through the input.
+    let mut reader = bytes;
+    let value = rmp_serde::from_read(&mut reader).map_err(|e| InputErrorKind::Decode {
+        format: Format::MessagePack.name(),
+        offset: Some(bytes.len() - reader.len()),
+        message: e.to_string(),
+    })?;
+    if !reader.is_empty() {
+        return Err(trailing_bytes(
+            Format::MessagePack,
+            bytes.len() - reader.len(),
+        ));
+    }
+    Ok(value)
+}
+
+#[cfg(not(feature = "msgpack"))]
+fn decode_msgpack<T: DeserializeOwned>(_bytes: &[u8]) -> Result<T, InputErrorKind> {
+    Err(InputErrorKind::UnsupportedFormat(
+        Format::MessagePack.name(),
+    ))
+}
@@ -2,40 +2,90 @@ use json::{self, JsonValue};
 
 use crate::{
     ipfs_host::{ipfs_close, ipfs_command, ipfs_read, ipfs_write},
-    IPFSErrorKind,
+    permissions::{self, Permission},
+    tar, Chunker, Cid, IPFSErrorKind, ImportOptions, ReadLoop, Secret, Version, Zeroize,
 };
 
-struct IPFSOptions {
+// A command of the IPFS http api, e.g. `files/mkdir` with its `arg`s.
+#[derive(Clone, Debug)]
+pub struct IpfsRequest {
     api: String,
     args: Vec<Arg>,
 }
 
+#[derive(Clone)]
 struct Arg {
     name: String,
     value: String,
+    // redacted in `Debug` and wiped on drop
+    secret: bool,
 }
 
-struct CommandResult {
-    status_code: u32,
-    resp_body: Option<Vec<u8>>,
+impl std::fmt::Debug for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value: &dyn std::fmt::Debug = if self.secret {
+            &Secret::<String>::default()
+        } else {
+            &self.value
+        };
+        f.debug_struct("Arg")
+            .field("name", &self.name)
+            .field("value", value)
+            .finish()
+    }
+}
+
+impl Drop for Arg {
+    fn drop(&mut self) {
+        if self.secret {
+            self.value.zeroize();
+        }
+    }
 }
 
-impl IPFSOptions {
-    fn new(api: &str) -> Self {
-        IPFSOptions {
+impl IpfsRequest {
+    pub fn new(api: &str) -> Self {
+        IpfsRequest {
             api: api.to_string(),
             args: Vec::new(),
         }
     }
 
-    fn add_arg(&mut self, name: &str, value: &str) {
+    pub fn add_arg(&mut self, name: &str, value: &str) {
         self.args.push(Arg {
             name: name.to_string(),
             value: value.to_string(),
+            secret: false,
         });
     }
 
-    fn to_json(&self) -> String {
+    // Adds an argument like a credential, which `Debug` doesn't print.
+    pub fn add_secret_arg(&mut self, name: &str, value: &Secret<String>) {
+        self.args.push(Arg {
+            name: name.to_string(),
+            value: value.expose_secret().clone(),
+            secret: true,
+        });
+    }
+
+    pub fn api(&self) -> &str {
+        &self.api
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.args
+            .iter()
+            .map(|arg| (arg.name.as_str(), arg.value.as_str()))
+    }
+
+    // The value of the first argument called `name`.
+    pub fn arg(&self, name: &str) -> Option<&str> {
+        self.args()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn to_json(&self) -> String {
         json::object! {
             api: self.api.clone(),
             args: self.args.iter().map(|arg| json::object! {
@@ -47,55 +97,104 @@ impl IPFSOptions {
     }
 }
 
-fn ipfs_command_result(opts: &IPFSOptions) -> Result<CommandResult, IPFSErrorKind> {
-    let result = ipfs_command_raw(opts)?;
-    let body = get_body(result.handle)?;
-    unsafe {
-        if ipfs_close(result.handle) != 0 {
-            return Err(IPFSErrorKind::InvalidHandle);
-        }
-    };
-    Ok(CommandResult {
-        status_code: result.code,
-        resp_body: Some(body),
-    })
+pub struct IpfsResponse {
+    pub status: u32,
+    pub body: Vec<u8>,
 }
 
-struct CommandRs {
-    code: u32,
-    handle: u32,
+// Sends a command to the IPFS api, with `body` as the request content.
+// Implement it to serve canned responses when running without a blockless
+// runtime.
+pub trait IpfsTransport {
+    fn call(
+        &self,
+        request: &IpfsRequest,
+        body: Option<&[u8]>,
+    ) -> Result<IpfsResponse, IPFSErrorKind>;
+
+    // Like `call`, but appends the response body to `buf` and returns the
+    // status. Override it to read the body straight into `buf`.
+    fn call_into(
+        &self,
+        request: &IpfsRequest,
+        body: Option<&[u8]>,
+        _read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<u32, IPFSErrorKind> {
+        let response = self.call(request, body)?;
+        buf.extend_from_slice(&response.body);
+        Ok(response.status)
+    }
 }
 
-fn ipfs_command_raw(opts: &IPFSOptions) -> Result<CommandRs, IPFSErrorKind> {
-    let opts_json = opts.to_json();
-    let mut handle: u32 = 0;
-    let mut code: u32 = 0;
-
-    let rs = unsafe {
-        ipfs_command(
-            opts_json.as_ptr(),
-            opts_json.len() as u32,
-            &mut handle,
-            &mut code,
-        )
-    };
-
-    if rs != 0 {
-        return Err(IPFSErrorKind::from(rs));
+// Sends commands through the `blockless_ipfs` host module.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HostIpfsTransport;
+
+impl IpfsTransport for HostIpfsTransport {
+    fn call(
+        &self,
+        request: &IpfsRequest,
+        body: Option<&[u8]>,
+    ) -> Result<IpfsResponse, IPFSErrorKind> {
+        let mut buf = Vec::new();
+        let status = self.call_into(request, body, &ReadLoop::new(), &mut buf)?;
+        Ok(IpfsResponse { status, body: buf })
     }
 
-    Ok(CommandRs { code, handle })
+    fn call_into(
+        &self,
+        request: &IpfsRequest,
+        body: Option<&[u8]>,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<u32, IPFSErrorKind> {
+        // wiped after the call, as the request may carry a secret argument
+        let opts_json = Secret::new(request.to_json());
+        let opts_json = opts_json.expose_secret();
+        let mut handle: u32 = 0;
+        let mut status: u32 = 0;
+        let rs = unsafe {
+            ipfs_command(
+                opts_json.as_ptr(),
+                opts_json.len() as u32,
+                &mut handle,
+                &mut status,
+            )
+        };
+        if rs != 0 {
+            return Err(IPFSErrorKind::from(rs));
+        }
+        let result = body
+            .map_or(Ok(0), |body| write_body(handle, body))
+            .and_then(|_| read_loop.read_to_end(buf, |chunk| read_body(handle, chunk)));
+        let rs = unsafe { ipfs_close(handle) };
+        result?;
+        if rs != 0 {
+            return Err(IPFSErrorKind::from(rs));
+        }
+        Ok(status)
+    }
 }
 
-fn write_body(h: u32, buf: &mut [u8]) -> Result<usize, IPFSErrorKind> {
-    let mut num: u32 = 0;
-    let rs = unsafe { ipfs_write(h, buf.as_mut_ptr() as *mut u32, buf.len() as u32, &mut num) };
-
-    if rs != 0 {
-        return Err(IPFSErrorKind::from(rs));
+// The host may accept only part of the buffer, so write until all of it is
+// sent. A write that makes no progress fails with `RequestError` rather
+// than looping forever.
+fn write_body(h: u32, buf: &[u8]) -> Result<usize, IPFSErrorKind> {
+    let mut written = 0;
+    while written < buf.len() {
+        let rest = &buf[written..];
+        let mut num: u32 = 0;
+        let rs = unsafe { ipfs_write(h, rest.as_ptr() as *mut u32, rest.len() as u32, &mut num) };
+        if rs != 0 {
+            return Err(IPFSErrorKind::from(rs));
+        }
+        if num == 0 || num as usize > rest.len() {
+            return Err(IPFSErrorKind::RequestError);
+        }
+        written += num as usize;
     }
-
-    Ok(num as usize)
+    Ok(written)
 }
 
 fn read_body(h: u32, buf: &mut [u8]) -> Result<usize, IPFSErrorKind> {
@@ -108,24 +207,12 @@ fn read_body(h: u32, buf: &mut [u8]) -> Result<usize, IPFSErrorKind> {
     Ok(num as usize)
 }
 
-fn get_body(h: u32) -> Result<Vec<u8>, IPFSErrorKind> {
-    let mut result = Vec::new();
-    let mut buf = vec![0; 1024];
-    loop {
-        let num = read_body(h, &mut buf)?;
-        if num == 0 {
-            break;
-        }
-        result.extend_from_slice(&buf[..num]);
-    }
-    Ok(result)
-}
-
 pub struct File {
     name: String,
     file_type: i64,
     size: i64,
-    hash: String,
+    // unset when listed without `FileListOptions::long`
+    hash: Option<Cid>,
 }
 
 impl std::fmt::Display for File {
@@ -133,37 +220,145 @@ impl std::fmt::Display for File {
         write!(
             f,
             "name:{}, size:{}, type:{}, hash:{}",
-            self.name, self.size, self.file_type, self.hash
+            self.name,
+            self.size,
+            self.file_type,
+            self.hash.as_ref().map(Cid::to_string).unwrap_or_default()
         )
     }
 }
 
-pub fn ipfs_create_dir(path: &str, parents: bool) -> Result<bool, IPFSErrorKind> {
-    let mut opts = IPFSOptions::new("files/mkdir");
-    opts.add_arg("arg", path);
-    opts.add_arg("parents", &parents.to_string());
-    let result = ipfs_command_result(&opts)?;
-    Ok(result.status_code == 200)
+impl File {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // 0 for files, 1 for directories.
+    pub fn file_type(&self) -> i64 {
+        self.file_type
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type == 1
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size.max(0) as u64
+    }
+
+    pub fn hash(&self) -> Option<&Cid> {
+        self.hash.as_ref()
+    }
 }
 
-pub fn ipfs_file_remove(path: &str, recursive: bool, force: bool) -> Result<bool, IPFSErrorKind> {
-    let mut opts = IPFSOptions::new("files/rm");
-    opts.add_arg("arg", path);
-    opts.add_arg("recursive", &recursive.to_string());
-    opts.add_arg("force", &force.to_string());
-    let result = ipfs_command_result(&opts)?;
-    Ok(result.status_code == 200)
+pub struct FileStat {
+    hash: Cid,
+    size: u64,
+    blocks: u64,
+    file_type: String,
+    cumulative_size: u64,
+    // only set by `FileStatOptions::with_local`
+    local: Option<bool>,
+    size_local: Option<u64>,
 }
 
-pub fn ipfs_file_copy(src: &str, dst: &str, parents: bool) -> Result<bool, IPFSErrorKind> {
-    let mut opts = IPFSOptions::new("files/cp");
-    opts.add_arg("arg", src);
-    opts.add_arg("arg", dst);
-    opts.add_arg("parents", &parents.to_string());
-    let result = ipfs_command_result(&opts)?;
-    Ok(result.status_code == 200)
+impl FileStat {
+    pub fn hash(&self) -> &Cid {
+        &self.hash
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    // `file` or `directory`.
+    pub fn file_type(&self) -> &str {
+        &self.file_type
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type == "directory"
+    }
+
+    pub fn cumulative_size(&self) -> u64 {
+        self.cumulative_size
+    }
+
+    // Whether all the blocks of the file are on the local node.
+    pub fn local(&self) -> Option<bool> {
+        self.local
+    }
+
+    // Bytes of the file's blocks that are on the local node.
+    pub fn size_local(&self) -> Option<u64> {
+        self.size_local
+    }
+}
+
+impl std::fmt::Display for FileStat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "hash:{}, size:{}, blocks:{}, type:{}, cumulative size:{}",
+            self.hash, self.size, self.blocks, self.file_type, self.cumulative_size
+        )
+    }
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct CreateDirOptions {
+    pub parents: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileRemoveOptions {
+    pub recursive: bool,
+    pub force: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileCopyOptions {
+    pub parents: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileStatOptions {
+    // Also reports how much of the file is on the local node, which the
+    // node computes by walking the whole dag.
+    pub with_local: bool,
+    // Asks for the size only.
+    pub size: bool,
+}
+
+// Changes the CID format of an MFS directory and the entries written to it
+// afterwards; unset options are left as they are.
+#[derive(Clone, Debug, Default)]
+pub struct ChangeCidOptions {
+    pub cid_version: Option<u32>,
+    // Multihash function name, e.g. `sha2-256`, passed to the node, which
+    // does the hashing.
+    pub hash: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileReadOptions {
+    pub offset: u64,
+    // Reads to the end of the file when unset.
+    pub count: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileListOptions {
+    // Fills in the size and hash of the entries.
+    pub long: bool,
+    pub unsorted: bool,
+}
+
+#[derive(Clone, Debug)]
 pub struct FileWriteOptions {
     pub file: String,
     pub offset: i64,
@@ -184,122 +379,511 @@ impl FileWriteOptions {
     }
 }
 
-pub fn ipfs_file_read(path: &str, offset: u64, buf: &mut [u8]) -> Result<usize, IPFSErrorKind> {
-    let mut opts = IPFSOptions::new("files/read");
-    opts.add_arg("arg", path);
-    opts.add_arg("offset", &offset.to_string());
-    opts.add_arg("count", &buf.len().to_string());
-    let rs = ipfs_command_raw(&opts)?;
-    let num = read_body(rs.handle, buf)?;
-    unsafe { ipfs_close(rs.handle) };
-    Ok(num)
-}
-
-pub fn ipfs_file_write(wopts: &FileWriteOptions, buf: &mut [u8]) -> Result<bool, IPFSErrorKind> {
-    let mut opts = IPFSOptions::new("files/write");
-    opts.add_arg("arg", &wopts.file);
-    opts.add_arg("offset", &wopts.offset.to_string());
-    opts.add_arg("create", &wopts.create.to_string());
-    opts.add_arg("parents", &wopts.parents.to_string());
-    opts.add_arg("truncate", &wopts.truncate.to_string());
-    let rs = ipfs_command_raw(&opts)?;
-    write_body(rs.handle, buf)?;
-    let _rbuf = get_body(rs.handle)?;
-    unsafe { ipfs_close(rs.handle) };
-    Ok(true)
+#[derive(Clone, Debug, Default)]
+pub struct AddOptions {
+    // Unset options use the defaults of the IPFS node.
+    pub pin: Option<bool>,
+    pub cid_version: Option<u32>,
+    pub raw_leaves: Option<bool>,
+    // e.g. `size-262144` or `rabin-262144-524288-1048576`
+    pub chunker: Option<String>,
+    // Computes the CID without storing the content.
+    pub only_hash: bool,
 }
 
-pub fn ipfs_file_list(path: Option<&str>) -> Result<Vec<File>, IPFSErrorKind> {
-    let mut opts = IPFSOptions::new("files/ls");
-    if let Some(p) = path {
-        opts.add_arg("args", p);
-    }
-    let result = ipfs_command_result(&opts)?;
-    if result.status_code != 200 {
-        return Err(IPFSErrorKind::RequestError);
-    }
-    let json_str =
-        String::from_utf8(result.resp_body.unwrap()).map_err(|_| IPFSErrorKind::Utf8Error)?;
-    let parsed = json::parse(&json_str).map_err(|_| IPFSErrorKind::InvalidParameter)?;
-    if !parsed["Entries"].is_array() {
-        return Err(IPFSErrorKind::InvalidParameter);
-    }
-    let files: Vec<File> = parsed["Entries"]
-        .members()
-        .filter_map(|v| {
-            Some(File {
-                name: v["Name"].as_str()?.to_string(),
-                file_type: v["Type"].as_i64()?,
-                size: v["Size"].as_i64()?,
-                hash: v["Hash"].as_str()?.to_string(),
-            })
+impl AddOptions {
+    // The options to compute the CID locally with `compute_cid`; `None`
+    // when the chunker or CID version isn't supported locally.
+    pub fn import_options(&self) -> Option<ImportOptions> {
+        let cid_version = match self.cid_version {
+            None | Some(0) => Version::V0,
+            Some(1) => Version::V1,
+            Some(_) => return None,
+        };
+        let chunker = match &self.chunker {
+            Some(chunker) => Chunker::parse(chunker)?,
+            None => Chunker::default(),
+        };
+        Some(ImportOptions {
+            cid_version,
+            raw_leaves: self.raw_leaves,
+            chunker,
         })
-        .collect();
-    Ok(files)
+    }
 }
 
-pub struct FileStat {
-    hash: String,
-    size: u64,
-    blocks: u64,
-    file_type: String,
-    cumulative_size: u64,
+#[derive(Clone, Debug)]
+pub struct Added {
+    pub name: String,
+    pub hash: Cid,
+    pub size: u64,
 }
 
-impl std::fmt::Display for FileStat {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "hash:{}, size:{}, blocks:{}, type:{}, cumulative size:{}",
-            self.hash, self.size, self.blocks, self.file_type, self.cumulative_size
-        )
+#[derive(Clone, Debug, Default)]
+pub struct CatOptions {
+    pub offset: u64,
+    // Reads to the end of the content when unset.
+    pub length: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GetEntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+// An entry of the content fetched by `get`. `path` starts with the
+// requested CID or name, e.g. `QmFoo/dir/file.txt`.
+#[derive(Clone, Debug)]
+pub struct GetEntry {
+    pub path: String,
+    pub kind: GetEntryKind,
+    pub link_target: Option<String>,
+    pub data: Vec<u8>,
+}
+
+// Runs IPFS commands through a transport: checks the permission manifest,
+// builds the request and turns a non-200 response into
+// `IPFSErrorKind::ApiError` with the message from the IPFS error body.
+pub struct IpfsClient<T = HostIpfsTransport> {
+    transport: T,
+}
+
+impl IpfsClient {
+    pub fn new() -> Self {
+        IpfsClient::with_transport(HostIpfsTransport)
     }
 }
 
-pub fn ipfs_file_stat(path: &str) -> Result<FileStat, IPFSErrorKind> {
-    let mut opts = IPFSOptions::new("files/stat");
-    opts.add_arg("arg", path);
-    let result = ipfs_command_result(&opts)?;
-    if result.status_code != 200 {
-        return Err(IPFSErrorKind::RequestError);
-    }
-    let json_str =
-        String::from_utf8(result.resp_body.unwrap()).map_err(|_| IPFSErrorKind::Utf8Error)?;
-    let parsed = json::parse(&json_str).map_err(|_| IPFSErrorKind::InvalidParameter)?;
-    Ok(FileStat {
-        hash: parsed["Hash"]
-            .as_str()
-            .ok_or(IPFSErrorKind::InvalidParameter)?
-            .to_string(),
-        size: parsed["Size"]
-            .as_u64()
-            .ok_or(IPFSErrorKind::InvalidParameter)?,
-        blocks: parsed["Blocks"]
-            .as_u64()
-            .ok_or(IPFSErrorKind::InvalidParameter)?,
-        file_type: parsed["Type"]
-            .as_str()
-            .ok_or(IPFSErrorKind::InvalidParameter)?
-            .to_string(),
-        cumulative_size: parsed["CumulativeSize"]
-            .as_u64()
-            .ok_or(IPFSErrorKind::InvalidParameter)?,
-    })
+impl Default for IpfsClient {
+    fn default() -> Self {
+        IpfsClient::new()
+    }
 }
 
-pub fn read_entire_file(path: &str) -> Result<Vec<u8>, IPFSErrorKind> {
-    let mut content = Vec::new();
-    let mut offset = 0;
-    loop {
-        let mut buffer = vec![0u8; 1024];
-        match ipfs_file_read(path, offset, &mut buffer) {
-            Ok(0) => break,
-            Ok(bytes_read) => {
-                content.extend_from_slice(&buffer[..bytes_read]);
-                offset += bytes_read as u64;
+impl<T: IpfsTransport> IpfsClient<T> {
+    pub fn with_transport(transport: T) -> Self {
+        IpfsClient { transport }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    // Sends `request` and returns the body of its successful response.
+    pub fn call(
+        &self,
+        request: &IpfsRequest,
+        body: Option<&[u8]>,
+    ) -> Result<Vec<u8>, IPFSErrorKind> {
+        let response = self.transport.call(request, body)?;
+        if response.status != 200 {
+            return Err(api_error(response));
+        }
+        Ok(response.body)
+    }
+
+    // Appends the body of the successful response to `buf` and returns the
+    // number of bytes appended. `buf` is left as it was on error.
+    pub fn call_into(
+        &self,
+        request: &IpfsRequest,
+        body: Option<&[u8]>,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, IPFSErrorKind> {
+        let start = buf.len();
+        let status = self
+            .transport
+            .call_into(request, body, read_loop, buf)
+            .inspect_err(|_| buf.truncate(start))?;
+        if status != 200 {
+            let body = buf.split_off(start);
+            return Err(api_error(IpfsResponse { status, body }));
+        }
+        Ok(buf.len() - start)
+    }
+
+    pub(crate) fn call_json(&self, request: &IpfsRequest) -> Result<JsonValue, IPFSErrorKind> {
+        let body = self.call(request, None)?;
+        let json_str = String::from_utf8(body).map_err(|_| IPFSErrorKind::Utf8Error)?;
+        json::parse(&json_str).map_err(|_| IPFSErrorKind::InvalidParameter)
+    }
+
+    pub fn create_dir(&self, path: &str, opts: &CreateDirOptions) -> Result<(), IPFSErrorKind> {
+        require_path(path)?;
+        let mut request = IpfsRequest::new("files/mkdir");
+        request.add_arg("arg", path);
+        request.add_arg("parents", &opts.parents.to_string());
+        self.call(&request, None)?;
+        Ok(())
+    }
+
+    pub fn remove(&self, path: &str, opts: &FileRemoveOptions) -> Result<(), IPFSErrorKind> {
+        require_path(path)?;
+        let mut request = IpfsRequest::new("files/rm");
+        request.add_arg("arg", path);
+        request.add_arg("recursive", &opts.recursive.to_string());
+        request.add_arg("force", &opts.force.to_string());
+        self.call(&request, None)?;
+        Ok(())
+    }
+
+    pub fn copy(&self, src: &str, dst: &str, opts: &FileCopyOptions) -> Result<(), IPFSErrorKind> {
+        require_path(src)?;
+        self.copy_to(src, dst, opts)
+    }
+
+    // `files/cp` checking the permission of `dst` only, for an `/ipfs/`
+    // source, which is content anyone can read.
+    pub(crate) fn copy_to(
+        &self,
+        src: &str,
+        dst: &str,
+        opts: &FileCopyOptions,
+    ) -> Result<(), IPFSErrorKind> {
+        require_path(dst)?;
+        let mut request = IpfsRequest::new("files/cp");
+        request.add_arg("arg", src);
+        request.add_arg("arg", dst);
+        request.add_arg("parents", &opts.parents.to_string());
+        self.call(&request, None)?;
+        Ok(())
+    }
+
+    pub fn read(&self, path: &str, opts: &FileReadOptions) -> Result<Vec<u8>, IPFSErrorKind> {
+        let mut data = Vec::new();
+        self.read_into(path, opts, &ReadLoop::new(), &mut data)?;
+        Ok(data)
+    }
+
+    // Appends the content read to `buf`, pre-sizing it from `opts.count`
+    // unless `read_loop` already carries a size hint.
+    pub fn read_into(
+        &self,
+        path: &str,
+        opts: &FileReadOptions,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, IPFSErrorKind> {
+        require_path(path)?;
+        let mut request = IpfsRequest::new("files/read");
+        request.add_arg("arg", path);
+        request.add_arg("offset", &opts.offset.to_string());
+        let mut read_loop = read_loop.clone();
+        if let Some(count) = opts.count {
+            request.add_arg("count", &count.to_string());
+            if read_loop.size_hint.is_none() {
+                read_loop.set_size_hint(count);
             }
-            Err(e) => return Err(e),
         }
+        self.call_into(&request, None, &read_loop, buf)
+    }
+
+    // Reads up to `buf.len()` bytes at `offset`, returning how many were read.
+    pub fn read_at(&self, path: &str, offset: u64, buf: &mut [u8]) -> Result<usize, IPFSErrorKind> {
+        let opts = FileReadOptions {
+            offset,
+            count: Some(buf.len() as u64),
+        };
+        let data = self.read(path, &opts)?;
+        let num = data.len().min(buf.len());
+        buf[..num].copy_from_slice(&data[..num]);
+        Ok(num)
+    }
+
+    pub fn write(&self, opts: &FileWriteOptions, data: &[u8]) -> Result<(), IPFSErrorKind> {
+        require_path(&opts.file)?;
+        let mut request = IpfsRequest::new("files/write");
+        request.add_arg("arg", &opts.file);
+        request.add_arg("offset", &opts.offset.to_string());
+        request.add_arg("create", &opts.create.to_string());
+        request.add_arg("parents", &opts.parents.to_string());
+        request.add_arg("truncate", &opts.truncate.to_string());
+        self.call(&request, Some(data))?;
+        Ok(())
+    }
+
+    pub fn list(
+        &self,
+        path: Option<&str>,
+        opts: &FileListOptions,
+    ) -> Result<Vec<File>, IPFSErrorKind> {
+        require_path(path.unwrap_or("/"))?;
+        let mut request = IpfsRequest::new("files/ls");
+        if let Some(p) = path {
+            request.add_arg("arg", p);
+        }
+        if opts.long {
+            request.add_arg("long", "true");
+        }
+        if opts.unsorted {
+            request.add_arg("U", "true");
+        }
+        let parsed = self.call_json(&request)?;
+        // an empty directory lists as `"Entries": null`
+        if parsed["Entries"].is_null() {
+            return Ok(Vec::new());
+        }
+        if !parsed["Entries"].is_array() {
+            return Err(IPFSErrorKind::InvalidParameter);
+        }
+        let files: Vec<File> = parsed["Entries"]
+            .members()
+            .filter_map(|v| {
+                Some(File {
+                    name: v["Name"].as_str()?.to_string(),
+                    file_type: v["Type"].as_i64()?,
+                    size: v["Size"].as_i64()?,
+                    hash: parse_cid(&v["Hash"]),
+                })
+            })
+            .collect();
+        Ok(files)
+    }
+
+    pub fn stat(&self, path: &str, opts: &FileStatOptions) -> Result<FileStat, IPFSErrorKind> {
+        require_path(path)?;
+        let mut request = IpfsRequest::new("files/stat");
+        request.add_arg("arg", path);
+        if opts.with_local {
+            request.add_arg("with-local", "true");
+        }
+        if opts.size {
+            request.add_arg("size", "true");
+        }
+        let parsed = self.call_json(&request)?;
+        let with_locality = parsed["WithLocality"].as_bool() == Some(true);
+        Ok(FileStat {
+            hash: parse_cid(&parsed["Hash"]).ok_or(IPFSErrorKind::InvalidParameter)?,
+            size: parsed["Size"]
+                .as_u64()
+                .ok_or(IPFSErrorKind::InvalidParameter)?,
+            blocks: parsed["Blocks"]
+                .as_u64()
+                .ok_or(IPFSErrorKind::InvalidParameter)?,
+            file_type: parsed["Type"]
+                .as_str()
+                .ok_or(IPFSErrorKind::InvalidParameter)?
+                .to_string(),
+            cumulative_size: parsed["CumulativeSize"]
+                .as_u64()
+                .ok_or(IPFSErrorKind::InvalidParameter)?,
+            local: parsed["Local"].as_bool().filter(|_| with_locality),
+            size_local: parsed["SizeLocal"].as_u64().filter(|_| with_locality),
+        })
+    }
+
+    // Moves or renames a file or directory.
+    pub fn rename(&self, src: &str, dst: &str) -> Result<(), IPFSErrorKind> {
+        require_path(src)?;
+        require_path(dst)?;
+        let mut request = IpfsRequest::new("files/mv");
+        request.add_arg("arg", src);
+        request.add_arg("arg", dst);
+        self.call(&request, None)?;
+        Ok(())
+    }
+
+    // Writes the changes of `path`, or of the whole MFS, to the blockstore
+    // and returns the CID of `path`.
+    pub fn flush(&self, path: Option<&str>) -> Result<Cid, IPFSErrorKind> {
+        require_path(path.unwrap_or("/"))?;
+        let mut request = IpfsRequest::new("files/flush");
+        if let Some(path) = path {
+            request.add_arg("arg", path);
+        }
+        let parsed = self.call_json(&request)?;
+        parse_cid(&parsed["Cid"]).ok_or(IPFSErrorKind::InvalidParameter)
     }
-    Ok(content)
+
+    pub fn change_cid(&self, path: &str, opts: &ChangeCidOptions) -> Result<(), IPFSErrorKind> {
+        require_path(path)?;
+        let mut request = IpfsRequest::new("files/chcid");
+        request.add_arg("arg", path);
+        if let Some(cid_version) = opts.cid_version {
+            request.add_arg("cid-version", &cid_version.to_string());
+        }
+        if let Some(hash) = &opts.hash {
+            request.add_arg("hash", hash);
+        }
+        self.call(&request, None)?;
+        Ok(())
+    }
+
+    // Adds `data` to IPFS and returns its CID.
+    pub fn add(&self, data: &[u8], opts: &AddOptions) -> Result<Added, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("add");
+        if let Some(pin) = opts.pin {
+            request.add_arg("pin", &pin.to_string());
+        }
+        if let Some(cid_version) = opts.cid_version {
+            request.add_arg("cid-version", &cid_version.to_string());
+        }
+        if let Some(raw_leaves) = opts.raw_leaves {
+            request.add_arg("raw-leaves", &raw_leaves.to_string());
+        }
+        if let Some(chunker) = &opts.chunker {
+            request.add_arg("chunker", chunker);
+        }
+        if opts.only_hash {
+            request.add_arg("only-hash", "true");
+        }
+        let body = self.call(&request, Some(data))?;
+        let body = String::from_utf8(body).map_err(|_| IPFSErrorKind::Utf8Error)?;
+        // one json object per line; progress lines carry no hash, the last
+        // line is the root
+        let added = body
+            .lines()
+            .rev()
+            .filter_map(|line| json::parse(line).ok())
+            .find(|line| line["Hash"].is_string())
+            .ok_or(IPFSErrorKind::InvalidParameter)?;
+        let hash = parse_cid(&added["Hash"]).ok_or(IPFSErrorKind::InvalidParameter)?;
+        let size = match &added["Size"] {
+            size if size.is_string() => size.as_str().and_then(|s| s.parse().ok()),
+            size => size.as_u64(),
+        };
+        Ok(Added {
+            name: added["Name"].as_str().unwrap_or_default().to_string(),
+            hash,
+            size: size.unwrap_or(0),
+        })
+    }
+
+    // Reads the content of a CID or an `/ipfs/` path.
+    pub fn cat(&self, path: &str, opts: &CatOptions) -> Result<Vec<u8>, IPFSErrorKind> {
+        let mut data = Vec::new();
+        self.cat_into(path, opts, &ReadLoop::new(), &mut data)?;
+        Ok(data)
+    }
+
+    // Appends the content to `buf`, pre-sizing it from `opts.length` unless
+    // `read_loop` already carries a size hint.
+    pub fn cat_into(
+        &self,
+        path: &str,
+        opts: &CatOptions,
+        read_loop: &ReadLoop,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("cat");
+        request.add_arg("arg", path);
+        if opts.offset > 0 {
+            request.add_arg("offset", &opts.offset.to_string());
+        }
+        let mut read_loop = read_loop.clone();
+        if let Some(length) = opts.length {
+            request.add_arg("length", &length.to_string());
+            if read_loop.size_hint.is_none() {
+                read_loop.set_size_hint(length);
+            }
+        }
+        self.call_into(&request, None, &read_loop, buf)
+    }
+
+    // Fetches a file or a whole directory tree by CID or `/ipfs/` path.
+    pub fn get(&self, path: &str) -> Result<Vec<GetEntry>, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("get");
+        request.add_arg("arg", path);
+        let archive = self.call(&request, None)?;
+        let entries = tar::entries(&archive).map_err(|_| IPFSErrorKind::InvalidEncoding)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| GetEntry {
+                path: entry.path,
+                kind: entry.kind,
+                link_target: entry.link_name,
+                data: entry.data.to_vec(),
+            })
+            .collect())
+    }
+
+    pub fn read_entire_file(&self, path: &str) -> Result<Vec<u8>, IPFSErrorKind> {
+        self.open_file(path)?.read_to_vec()
+    }
+}
+
+pub(crate) fn parse_cid(value: &JsonValue) -> Option<Cid> {
+    value.as_str()?.parse().ok()
+}
+
+// Decodes the error body of the IPFS api, `{"Message": "...", "Code": 0}`.
+fn api_error(response: IpfsResponse) -> IPFSErrorKind {
+    let body = String::from_utf8_lossy(&response.body);
+    match json::parse(&body) {
+        Ok(parsed) if parsed["Message"].is_string() => IPFSErrorKind::ApiError {
+            status: response.status,
+            code: parsed["Code"].as_i64(),
+            message: parsed["Message"].as_str().unwrap_or_default().to_string(),
+        },
+        _ => IPFSErrorKind::ApiError {
+            status: response.status,
+            code: None,
+            message: body.trim().to_string(),
+        },
+    }
+}
+
+fn require_path(path: &str) -> Result<(), IPFSErrorKind> {
+    permissions::require(Permission::IpfsPath(path.to_string()))
+        .map_err(IPFSErrorKind::MissingPermission)
+}
+
+pub fn ipfs_create_dir(path: &str, parents: bool) -> Result<bool, IPFSErrorKind> {
+    IpfsClient::new().create_dir(path, &CreateDirOptions { parents })?;
+    Ok(true)
+}
+
+pub fn ipfs_file_remove(path: &str, recursive: bool, force: bool) -> Result<bool, IPFSErrorKind> {
+    IpfsClient::new().remove(path, &FileRemoveOptions { recursive, force })?;
+    Ok(true)
+}
+
+pub fn ipfs_file_copy(src: &str, dst: &str, parents: bool) -> Result<bool, IPFSErrorKind> {
+    IpfsClient::new().copy(src, dst, &FileCopyOptions { parents })?;
+    Ok(true)
+}
+
+pub fn ipfs_file_read(path: &str, offset: u64, buf: &mut [u8]) -> Result<usize, IPFSErrorKind> {
+    IpfsClient::new().read_at(path, offset, buf)
+}
+
+pub fn ipfs_file_write(wopts: &FileWriteOptions, buf: &[u8]) -> Result<bool, IPFSErrorKind> {
+    IpfsClient::new().write(wopts, buf)?;
+    Ok(true)
+}
+
+pub fn ipfs_file_list(path: Option<&str>) -> Result<Vec<File>, IPFSErrorKind> {
+    IpfsClient::new().list(path, &FileListOptions::default())
+}
+
+pub fn ipfs_file_move(src: &str, dst: &str) -> Result<bool, IPFSErrorKind> {
+    IpfsClient::new().rename(src, dst)?;
+    Ok(true)
+}
+
+pub fn ipfs_file_stat(path: &str) -> Result<FileStat, IPFSErrorKind> {
+    IpfsClient::new().stat(path, &FileStatOptions::default())
+}
+
+pub fn ipfs_file_flush(path: Option<&str>) -> Result<Cid, IPFSErrorKind> {
+    IpfsClient::new().flush(path)
+}
+
+pub fn read_entire_file(path: &str) -> Result<Vec<u8>, IPFSErrorKind> {
+    IpfsClient::new().read_entire_file(path)
+}
+
+pub fn ipfs_add(data: &[u8], opts: &AddOptions) -> Result<Added, IPFSErrorKind> {
+    IpfsClient::new().add(data, opts)
+}
+
+pub fn ipfs_cat(path: &str, offset: u64, length: Option<u64>) -> Result<Vec<u8>, IPFSErrorKind> {
+    IpfsClient::new().cat(path, &CatOptions { offset, length })
+}
+
+pub fn ipfs_get(path: &str) -> Result<Vec<GetEntry>, IPFSErrorKind> {
+    IpfsClient::new().get(path)
 }
@@ -0,0 +1,130 @@
+use json::JsonValue;
+
+use crate::{Cid, DagCodec, IPFSErrorKind, IpfsClient, IpfsRequest, IpfsTransport, Ipld};
+
+#[derive(Clone, Debug)]
+pub struct DagPutOptions {
+    // Codec the node is stored with; the CID uses it.
+    pub store_codec: DagCodec,
+    // Codec the node is sent with.
+    pub input_codec: DagCodec,
+    pub pin: Option<bool>,
+    // Multihash function name, e.g. `sha2-256`, passed to the node, which
+    // does the hashing.
+    pub hash: Option<String>,
+}
+
+impl Default for DagPutOptions {
+    fn default() -> Self {
+        DagPutOptions {
+            store_codec: DagCodec::DagCbor,
+            input_codec: DagCodec::DagJson,
+            pin: None,
+            hash: None,
+        }
+    }
+}
+
+// The node a path resolves to, and what's left of the path within it.
+#[derive(Clone, Debug)]
+pub struct DagResolved {
+    pub cid: Cid,
+    pub rem_path: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct DagStat {
+    pub size: u64,
+    pub num_blocks: u64,
+}
+
+impl<T: IpfsTransport> IpfsClient<T> {
+    pub fn dag_put(&self, node: &Ipld, opts: &DagPutOptions) -> Result<Cid, IPFSErrorKind> {
+        let body = node.encode(opts.input_codec)?;
+        let mut request = IpfsRequest::new("dag/put");
+        request.add_arg("store-codec", opts.store_codec.as_str());
+        request.add_arg("input-codec", opts.input_codec.as_str());
+        if let Some(pin) = opts.pin {
+            request.add_arg("pin", &pin.to_string());
+        }
+        if let Some(hash) = &opts.hash {
+            request.add_arg("hash", hash);
+        }
+        let body = self.call(&request, Some(&body))?;
+        let parsed = parse_json(body)?;
+        link(&parsed["Cid"]).ok_or(IPFSErrorKind::InvalidParameter)
+    }
+
+    // Gets the node at a CID or a path through it, e.g. `<cid>/items/0`,
+    // which the node resolves across links.
+    pub fn dag_get(&self, path: &str) -> Result<Ipld, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("dag/get");
+        request.add_arg("arg", path);
+        request.add_arg("output-codec", DagCodec::DagJson.as_str());
+        let body = self.call(&request, None)?;
+        Ok(Ipld::decode(DagCodec::DagJson, &body)?)
+    }
+
+    pub fn dag_resolve(&self, path: &str) -> Result<DagResolved, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("dag/resolve");
+        request.add_arg("arg", path);
+        let parsed = self.call_json(&request)?;
+        Ok(DagResolved {
+            cid: link(&parsed["Cid"]).ok_or(IPFSErrorKind::InvalidParameter)?,
+            rem_path: parsed["RemPath"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+
+    pub fn dag_stat(&self, cid: &Cid) -> Result<DagStat, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("dag/stat");
+        request.add_arg("arg", &cid.to_string());
+        request.add_arg("progress", "false");
+        let body = self.call(&request, None)?;
+        let body = String::from_utf8(body).map_err(|_| IPFSErrorKind::Utf8Error)?;
+        // the last json object is the final result; newer nodes report the
+        // stats per root in `DagStats`
+        let parsed = body
+            .lines()
+            .rev()
+            .find_map(|line| json::parse(line).ok())
+            .ok_or(IPFSErrorKind::InvalidParameter)?;
+        let stat = match &parsed["DagStats"][0] {
+            JsonValue::Null => &parsed,
+            stat => stat,
+        };
+        Ok(DagStat {
+            size: stat["Size"]
+                .as_u64()
+                .ok_or(IPFSErrorKind::InvalidParameter)?,
+            num_blocks: stat["NumBlocks"]
+                .as_u64()
+                .ok_or(IPFSErrorKind::InvalidParameter)?,
+        })
+    }
+}
+
+fn parse_json(body: Vec<u8>) -> Result<JsonValue, IPFSErrorKind> {
+    let body = String::from_utf8(body).map_err(|_| IPFSErrorKind::Utf8Error)?;
+    json::parse(&body).map_err(|_| IPFSErrorKind::InvalidParameter)
+}
+
+// CIDs in dag responses are dag-json links, `{"/": "<cid>"}`.
+fn link(value: &JsonValue) -> Option<Cid> {
+    value["/"].as_str()?.parse().ok()
+}
+
+pub fn ipfs_dag_put(node: &Ipld, opts: &DagPutOptions) -> Result<Cid, IPFSErrorKind> {
+    IpfsClient::new().dag_put(node, opts)
+}
+
+pub fn ipfs_dag_get(path: &str) -> Result<Ipld, IPFSErrorKind> {
+    IpfsClient::new().dag_get(path)
+}
+
+pub fn ipfs_dag_resolve(path: &str) -> Result<DagResolved, IPFSErrorKind> {
+    IpfsClient::new().dag_resolve(path)
+}
+
+pub fn ipfs_dag_stat(cid: &Cid) -> Result<DagStat, IPFSErrorKind> {
+    IpfsClient::new().dag_stat(cid)
+}
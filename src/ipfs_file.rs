@@ -0,0 +1,269 @@
+use std::io::{self, SeekFrom};
+
+use crate::{
+    FileReadOptions, FileStatOptions, FileWriteOptions, HostIpfsTransport, IPFSErrorKind,
+    IpfsClient, IpfsTransport,
+};
+
+pub const DEFAULT_WRITE_CHUNK_SIZE: usize = 1048576;
+pub const DEFAULT_READ_WINDOW: usize = 4 * 1048576;
+
+// Writes an MFS file in chunks, one `files/write` call per chunk at the
+// offset following the previous one, so the whole content never has to be
+// in memory. `create`, `parents` and `truncate` only apply to the first
+// call; later calls append to what it wrote.
+//
+// Call `finish` to send the last chunk and get its errors; dropping the
+// writer sends it too but ignores them, like `std::io::BufWriter`. A chunk
+// `finish` failed to send is not sent again on drop.
+pub struct IpfsFileWriter<'a, T: IpfsTransport = HostIpfsTransport> {
+    client: &'a IpfsClient<T>,
+    opts: FileWriteOptions,
+    chunk_size: usize,
+    buffer: Vec<u8>,
+    written: u64,
+    started: bool,
+    finished: bool,
+    progress: Option<Box<dyn FnMut(u64) + 'a>>,
+}
+
+impl<'a, T: IpfsTransport> IpfsFileWriter<'a, T> {
+    pub fn new(client: &'a IpfsClient<T>, opts: FileWriteOptions) -> Self {
+        IpfsFileWriter {
+            client,
+            opts,
+            chunk_size: DEFAULT_WRITE_CHUNK_SIZE,
+            buffer: Vec::new(),
+            written: 0,
+            started: false,
+            finished: false,
+            progress: None,
+        }
+    }
+
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size.max(1);
+    }
+
+    // Called with the total number of bytes written after every chunk.
+    pub fn set_progress(&mut self, progress: impl FnMut(u64) + 'a) {
+        self.progress = Some(Box::new(progress));
+    }
+
+    // Bytes written to the file so far, not counting buffered ones.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    // Writes `data` as a single chunk, after any buffered bytes.
+    pub fn write_chunk(&mut self, data: &[u8]) -> Result<(), IPFSErrorKind> {
+        self.flush_buffer()?;
+        self.send(data)
+    }
+
+    // Sends the buffered bytes and returns the total written. A file that
+    // got no data is still created or truncated as the options ask.
+    pub fn finish(mut self) -> Result<u64, IPFSErrorKind> {
+        // the caller gets the error; dropping must not send the chunk again
+        self.finished = true;
+        self.flush_buffer()?;
+        if !self.started && (self.opts.create || self.opts.truncate) {
+            self.send(&[])?;
+        }
+        Ok(self.written)
+    }
+
+    fn flush_buffer(&mut self) -> Result<(), IPFSErrorKind> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let buffer = std::mem::take(&mut self.buffer);
+        let result = self.send(&buffer);
+        self.buffer = buffer;
+        // a failed chunk stays buffered for a retry
+        if result.is_ok() {
+            self.buffer.clear();
+        }
+        result
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), IPFSErrorKind> {
+        let first = !self.started;
+        let opts = FileWriteOptions {
+            file: self.opts.file.clone(),
+            offset: self.opts.offset + self.written as i64,
+            create: first && self.opts.create,
+            parents: first && self.opts.parents,
+            truncate: first && self.opts.truncate,
+        };
+        self.client.write(&opts, data)?;
+        self.started = true;
+        self.written += data.len() as u64;
+        if let Some(progress) = &mut self.progress {
+            progress(self.written);
+        }
+        Ok(())
+    }
+}
+
+impl<T: IpfsTransport> io::Write for IpfsFileWriter<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buffer.len() >= self.chunk_size {
+            self.flush_buffer().map_err(io::Error::other)?;
+        }
+        // big writes skip the buffer
+        if self.buffer.is_empty() && buf.len() >= self.chunk_size {
+            self.send(&buf[..self.chunk_size])
+                .map_err(io::Error::other)?;
+            return Ok(self.chunk_size);
+        }
+        let n = (self.chunk_size - self.buffer.len()).min(buf.len());
+        self.buffer.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_buffer().map_err(io::Error::other)
+    }
+}
+
+impl<T: IpfsTransport> Drop for IpfsFileWriter<'_, T> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.flush_buffer();
+        }
+    }
+}
+
+// Reads an MFS file through `std::io::Read` and `std::io::Seek`. The size
+// comes from `files/stat` when the file is opened; reads fetch a whole
+// window with one `files/read` call and serve the following reads from it.
+pub struct IpfsFileReader<'a, T: IpfsTransport = HostIpfsTransport> {
+    client: &'a IpfsClient<T>,
+    path: String,
+    size: u64,
+    pos: u64,
+    window: usize,
+    buffer: Vec<u8>,
+    // file offset of `buffer[0]`
+    buffer_start: u64,
+}
+
+impl<'a, T: IpfsTransport> IpfsFileReader<'a, T> {
+    pub fn open(client: &'a IpfsClient<T>, path: &str) -> Result<Self, IPFSErrorKind> {
+        let stat = client.stat(path, &FileStatOptions::default())?;
+        Ok(IpfsFileReader {
+            client,
+            path: path.to_string(),
+            size: stat.size(),
+            pos: 0,
+            window: DEFAULT_READ_WINDOW,
+            buffer: Vec::new(),
+            buffer_start: 0,
+        })
+    }
+
+    pub fn set_window_size(&mut self, window: usize) {
+        self.window = window.max(1);
+    }
+
+    // Size of the file when it was opened.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    // Reads `len` bytes at `offset`, or up to the end of the file, in
+    // window-sized calls. Doesn't move the position.
+    pub fn read_range(&self, offset: u64, len: u64) -> Result<Vec<u8>, IPFSErrorKind> {
+        let end = offset.saturating_add(len).min(self.size);
+        let mut data = Vec::with_capacity(end.saturating_sub(offset) as usize);
+        let mut offset = offset;
+        while offset < end {
+            let count = (end - offset).min(self.window as u64);
+            let chunk = self.fetch(offset, count)?;
+            if chunk.is_empty() {
+                // the file shrank since it was opened
+                break;
+            }
+            offset += chunk.len() as u64;
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+
+    pub fn read_to_vec(&mut self) -> Result<Vec<u8>, IPFSErrorKind> {
+        let data = self.read_range(self.pos, self.size.saturating_sub(self.pos))?;
+        self.pos += data.len() as u64;
+        Ok(data)
+    }
+
+    fn fetch(&self, offset: u64, count: u64) -> Result<Vec<u8>, IPFSErrorKind> {
+        let opts = FileReadOptions {
+            offset,
+            count: Some(count),
+        };
+        self.client.read(&self.path, &opts)
+    }
+
+    fn buffered(&self) -> Option<&[u8]> {
+        let start = self.pos.checked_sub(self.buffer_start)? as usize;
+        self.buffer.get(start..).filter(|rest| !rest.is_empty())
+    }
+}
+
+impl<T: IpfsTransport> io::Read for IpfsFileReader<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.size {
+            return Ok(0);
+        }
+        if self.buffered().is_none() {
+            let remaining = self.size - self.pos;
+            // big reads skip the window
+            if buf.len() >= self.window {
+                let count = remaining.min(buf.len() as u64);
+                let data = self.fetch(self.pos, count).map_err(io::Error::other)?;
+                let num = data.len().min(buf.len());
+                buf[..num].copy_from_slice(&data[..num]);
+                self.pos += num as u64;
+                return Ok(num);
+            }
+            let count = remaining.min(self.window as u64);
+            self.buffer = self.fetch(self.pos, count).map_err(io::Error::other)?;
+            self.buffer_start = self.pos;
+        }
+        let Some(buffered) = self.buffered() else {
+            return Ok(0);
+        };
+        let num = buffered.len().min(buf.len());
+        buf[..num].copy_from_slice(&buffered[..num]);
+        self.pos += num as u64;
+        Ok(num)
+    }
+}
+
+impl<T: IpfsTransport> io::Seek for IpfsFileReader<'_, T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek before the start of the file",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+impl<T: IpfsTransport> IpfsClient<T> {
+    pub fn file_writer(&self, opts: FileWriteOptions) -> IpfsFileWriter<'_, T> {
+        IpfsFileWriter::new(self, opts)
+    }
+
+    pub fn open_file(&self, path: &str) -> Result<IpfsFileReader<'_, T>, IPFSErrorKind> {
+        IpfsFileReader::open(self, path)
+    }
+}
@@ -0,0 +1,330 @@
+use json::JsonValue;
+
+use crate::{ipfs::parse_cid, Cid, IPFSErrorKind, IpfsClient, IpfsRequest, IpfsTransport, Secret};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinType {
+    Direct,
+    Recursive,
+    Indirect,
+    All,
+}
+
+impl PinType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PinType::Direct => "direct",
+            PinType::Recursive => "recursive",
+            PinType::Indirect => "indirect",
+            PinType::All => "all",
+        }
+    }
+
+    fn parse(s: &str) -> Option<PinType> {
+        match s {
+            "direct" => Some(PinType::Direct),
+            "recursive" => Some(PinType::Recursive),
+            "indirect" => Some(PinType::Indirect),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Pin {
+    pub cid: Cid,
+    pub pin_type: PinType,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PinLsOptions {
+    // Lists only the pins of this CID or path.
+    pub path: Option<String>,
+    pub pin_type: Option<PinType>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemotePinStatus {
+    Queued,
+    Pinning,
+    Pinned,
+    Failed,
+}
+
+impl RemotePinStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RemotePinStatus::Queued => "queued",
+            RemotePinStatus::Pinning => "pinning",
+            RemotePinStatus::Pinned => "pinned",
+            RemotePinStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(s: &str) -> Option<RemotePinStatus> {
+        match s {
+            "queued" => Some(RemotePinStatus::Queued),
+            "pinning" => Some(RemotePinStatus::Pinning),
+            "pinned" => Some(RemotePinStatus::Pinned),
+            "failed" => Some(RemotePinStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RemotePin {
+    pub cid: Cid,
+    pub name: String,
+    pub status: RemotePinStatus,
+}
+
+#[derive(Clone, Debug)]
+pub struct RemoteService {
+    pub service: String,
+    pub endpoint: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct RemotePinAddOptions {
+    pub service: String,
+    pub name: Option<String>,
+    // Returns as soon as the service queued the pin instead of waiting for
+    // it to be pinned.
+    pub background: bool,
+}
+
+impl RemotePinAddOptions {
+    pub fn new(service: &str) -> Self {
+        RemotePinAddOptions {
+            service: service.to_string(),
+            name: None,
+            background: false,
+        }
+    }
+}
+
+// Selects remote pins for `pin_remote_ls` and `pin_remote_rm`. The service
+// defaults to listing pinned content only.
+#[derive(Clone, Debug)]
+pub struct RemotePinQuery {
+    pub service: String,
+    pub name: Option<String>,
+    pub cids: Vec<Cid>,
+    pub status: Vec<RemotePinStatus>,
+}
+
+impl RemotePinQuery {
+    pub fn new(service: &str) -> Self {
+        RemotePinQuery {
+            service: service.to_string(),
+            name: None,
+            cids: Vec::new(),
+            status: Vec::new(),
+        }
+    }
+
+    fn add_args(&self, request: &mut IpfsRequest) {
+        request.add_arg("service", &self.service);
+        if let Some(name) = &self.name {
+            request.add_arg("name", name);
+        }
+        if !self.cids.is_empty() {
+            let cids = self
+                .cids
+                .iter()
+                .map(Cid::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            request.add_arg("cid", &cids);
+        }
+        if !self.status.is_empty() {
+            let status = self
+                .status
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(",");
+            request.add_arg("status", &status);
+        }
+    }
+}
+
+impl<T: IpfsTransport> IpfsClient<T> {
+    // Pins a CID or path, returning the pinned CIDs.
+    pub fn pin_add(&self, path: &str, recursive: bool) -> Result<Vec<Cid>, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/add");
+        request.add_arg("arg", path);
+        request.add_arg("recursive", &recursive.to_string());
+        let parsed = self.call_json(&request)?;
+        Ok(pins(&parsed["Pins"]))
+    }
+
+    pub fn pin_rm(&self, path: &str, recursive: bool) -> Result<Vec<Cid>, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/rm");
+        request.add_arg("arg", path);
+        request.add_arg("recursive", &recursive.to_string());
+        let parsed = self.call_json(&request)?;
+        Ok(pins(&parsed["Pins"]))
+    }
+
+    pub fn pin_ls(&self, opts: &PinLsOptions) -> Result<Vec<Pin>, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/ls");
+        if let Some(path) = &opts.path {
+            request.add_arg("arg", path);
+        }
+        if let Some(pin_type) = opts.pin_type {
+            request.add_arg("type", pin_type.as_str());
+        }
+        let parsed = self.call_json(&request)?;
+        if !parsed["Keys"].is_object() {
+            return Err(IPFSErrorKind::InvalidParameter);
+        }
+        Ok(parsed["Keys"]
+            .entries()
+            .filter_map(|(cid, pin)| {
+                Some(Pin {
+                    cid: cid.parse().ok()?,
+                    pin_type: PinType::parse(pin["Type"].as_str()?)?,
+                })
+            })
+            .collect())
+    }
+
+    // Moves a recursive pin from `from` to `to`, e.g. to the new version of
+    // a directory, keeping the pin on `from` unless `unpin` is set.
+    pub fn pin_update(&self, from: &str, to: &str, unpin: bool) -> Result<Vec<Cid>, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/update");
+        request.add_arg("arg", from);
+        request.add_arg("arg", to);
+        request.add_arg("unpin", &unpin.to_string());
+        let parsed = self.call_json(&request)?;
+        Ok(pins(&parsed["Pins"]))
+    }
+
+    pub fn pin_remote_service_add(
+        &self,
+        service: &str,
+        endpoint: &str,
+        key: &Secret<String>,
+    ) -> Result<(), IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/remote/service/add");
+        request.add_arg("arg", service);
+        request.add_arg("arg", endpoint);
+        request.add_secret_arg("arg", key);
+        self.call(&request, None)?;
+        Ok(())
+    }
+
+    pub fn pin_remote_service_rm(&self, service: &str) -> Result<(), IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/remote/service/rm");
+        request.add_arg("arg", service);
+        self.call(&request, None)?;
+        Ok(())
+    }
+
+    pub fn pin_remote_service_ls(&self) -> Result<Vec<RemoteService>, IPFSErrorKind> {
+        let request = IpfsRequest::new("pin/remote/service/ls");
+        let parsed = self.call_json(&request)?;
+        Ok(parsed["RemoteServices"]
+            .members()
+            .filter_map(|service| {
+                Some(RemoteService {
+                    service: service["Service"].as_str()?.to_string(),
+                    endpoint: service["ApiEndpoint"].as_str()?.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    pub fn pin_remote_add(
+        &self,
+        path: &str,
+        opts: &RemotePinAddOptions,
+    ) -> Result<RemotePin, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/remote/add");
+        request.add_arg("arg", path);
+        request.add_arg("service", &opts.service);
+        if let Some(name) = &opts.name {
+            request.add_arg("name", name);
+        }
+        if opts.background {
+            request.add_arg("background", "true");
+        }
+        let parsed = self.call_json(&request)?;
+        remote_pin(&parsed).ok_or(IPFSErrorKind::InvalidParameter)
+    }
+
+    pub fn pin_remote_ls(&self, query: &RemotePinQuery) -> Result<Vec<RemotePin>, IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/remote/ls");
+        query.add_args(&mut request);
+        let body = self.call(&request, None)?;
+        let body = String::from_utf8(body).map_err(|_| IPFSErrorKind::Utf8Error)?;
+        // one json object per pin
+        body.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                json::parse(line)
+                    .ok()
+                    .as_ref()
+                    .and_then(remote_pin)
+                    .ok_or(IPFSErrorKind::InvalidParameter)
+            })
+            .collect()
+    }
+
+    // Removes the matching remote pins. Without `force`, fails when the
+    // query matches more than one pin.
+    pub fn pin_remote_rm(&self, query: &RemotePinQuery, force: bool) -> Result<(), IPFSErrorKind> {
+        let mut request = IpfsRequest::new("pin/remote/rm");
+        query.add_args(&mut request);
+        if force {
+            request.add_arg("force", "true");
+        }
+        self.call(&request, None)?;
+        Ok(())
+    }
+}
+
+fn pins(value: &JsonValue) -> Vec<Cid> {
+    value.members().filter_map(parse_cid).collect()
+}
+
+fn remote_pin(value: &JsonValue) -> Option<RemotePin> {
+    Some(RemotePin {
+        cid: parse_cid(&value["Cid"])?,
+        name: value["Name"].as_str().unwrap_or_default().to_string(),
+        status: RemotePinStatus::parse(value["Status"].as_str()?)?,
+    })
+}
+
+pub fn ipfs_pin_add(path: &str, recursive: bool) -> Result<Vec<Cid>, IPFSErrorKind> {
+    IpfsClient::new().pin_add(path, recursive)
+}
+
+pub fn ipfs_pin_rm(path: &str, recursive: bool) -> Result<Vec<Cid>, IPFSErrorKind> {
+    IpfsClient::new().pin_rm(path, recursive)
+}
+
+pub fn ipfs_pin_ls(opts: &PinLsOptions) -> Result<Vec<Pin>, IPFSErrorKind> {
+    IpfsClient::new().pin_ls(opts)
+}
+
+pub fn ipfs_pin_update(from: &str, to: &str, unpin: bool) -> Result<Vec<Cid>, IPFSErrorKind> {
+    IpfsClient::new().pin_update(from, to, unpin)
+}
+
+pub fn ipfs_pin_remote_add(
+    path: &str,
+    opts: &RemotePinAddOptions,
+) -> Result<RemotePin, IPFSErrorKind> {
+    IpfsClient::new().pin_remote_add(path, opts)
+}
+
+pub fn ipfs_pin_remote_ls(query: &RemotePinQuery) -> Result<Vec<RemotePin>, IPFSErrorKind> {
+    IpfsClient::new().pin_remote_ls(query)
+}
+
+pub fn ipfs_pin_remote_rm(query: &RemotePinQuery, force: bool) -> Result<(), IPFSErrorKind> {
+    IpfsClient::new().pin_remote_rm(query, force)
+}
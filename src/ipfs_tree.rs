@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+use json::JsonValue;
+
+use crate::{
+    ipfs::parse_cid, Cid, FileCopyOptions, FileListOptions, HostIpfsTransport, IPFSErrorKind,
+    IpfsClient, IpfsRequest, IpfsTransport,
+};
+
+// An entry found by `walk`. `path` is the full path, starting with the
+// walked MFS path or `/ipfs/<cid>`; `depth` is 1 for the direct children of
+// the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    pub is_dir: bool,
+    pub size: u64,
+    pub hash: Option<Cid>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+// A difference found by `diff`, at `path` relative to the compared roots.
+// An added or removed directory is a single change.
+#[derive(Clone, Debug)]
+pub struct TreeChange {
+    pub path: String,
+    pub kind: TreeChangeKind,
+    pub before: Option<TreeEntry>,
+    pub after: Option<TreeEntry>,
+}
+
+type EntryFilter<'a> = Box<dyn Fn(&TreeEntry) -> bool + 'a>;
+
+// Walks a directory tree depth first, listing each directory when it is
+// reached. MFS paths are listed with `files/ls`; CIDs and `/ipfs/` paths
+// with `ls`.
+pub struct TreeWalker<'a, T: IpfsTransport = HostIpfsTransport> {
+    client: &'a IpfsClient<T>,
+    root: Option<String>,
+    // entries still to yield, the next one last
+    stack: Vec<TreeEntry>,
+    max_depth: Option<usize>,
+    filter: Option<EntryFilter<'a>>,
+    prune: Option<EntryFilter<'a>>,
+    error: Option<IPFSErrorKind>,
+}
+
+impl<'a, T: IpfsTransport> TreeWalker<'a, T> {
+    pub fn new(client: &'a IpfsClient<T>, path: &str) -> Self {
+        TreeWalker {
+            client,
+            root: Some(content_path(path)),
+            stack: Vec::new(),
+            max_depth: None,
+            filter: None,
+            prune: None,
+            error: None,
+        }
+    }
+
+    // Doesn't list directories deeper than `max_depth`; 1 lists the root
+    // only.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = Some(max_depth);
+    }
+
+    // Yields only the entries `filter` accepts. Directories it rejects are
+    // still walked.
+    pub fn set_filter(&mut self, filter: impl Fn(&TreeEntry) -> bool + 'a) {
+        self.filter = Some(Box::new(filter));
+    }
+
+    // Doesn't walk into the directories `prune` accepts.
+    pub fn set_prune(&mut self, prune: impl Fn(&TreeEntry) -> bool + 'a) {
+        self.prune = Some(Box::new(prune));
+    }
+
+    fn push_children(&mut self, path: &str, depth: usize) -> Result<(), IPFSErrorKind> {
+        let children = list_dir(self.client, path, depth)?;
+        self.stack.extend(children.into_iter().rev());
+        Ok(())
+    }
+
+    fn expands(&self, entry: &TreeEntry) -> bool {
+        entry.is_dir
+            && self.max_depth.is_none_or(|max| entry.depth < max)
+            && !self.prune.as_ref().is_some_and(|prune| prune(entry))
+    }
+}
+
+impl<T: IpfsTransport> Iterator for TreeWalker<'_, T> {
+    type Item = Result<TreeEntry, IPFSErrorKind>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        if let Some(root) = self.root.take() {
+            if self.max_depth != Some(0) {
+                if let Err(e) = self.push_children(&root, 1) {
+                    return Some(Err(e));
+                }
+            }
+        }
+        loop {
+            let entry = self.stack.pop()?;
+            if self.expands(&entry) {
+                // a failed listing is reported after its directory
+                if let Err(e) = self.push_children(&entry.path, entry.depth + 1) {
+                    self.error = Some(e);
+                }
+            }
+            if self.filter.as_ref().is_none_or(|filter| filter(&entry)) {
+                return Some(Ok(entry));
+            }
+            if let Some(error) = self.error.take() {
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+impl<T: IpfsTransport> IpfsClient<T> {
+    // Walks the tree under an MFS path, a CID or an `/ipfs/` path.
+    pub fn walk(&self, path: &str) -> TreeWalker<'_, T> {
+        TreeWalker::new(self, path)
+    }
+
+    // Total size of the files under `path`.
+    pub fn tree_size(&self, path: &str) -> Result<u64, IPFSErrorKind> {
+        let mut size = 0;
+        for entry in self.walk(path) {
+            let entry = entry?;
+            if !entry.is_dir {
+                size += entry.size;
+            }
+        }
+        Ok(size)
+    }
+
+    // Copies the tree of `cid` into the MFS at `dst`. The MFS links to the
+    // existing dag, so nothing is fetched until it is read. Only `dst` needs
+    // a permission.
+    pub fn copy_from_cid(
+        &self,
+        cid: &Cid,
+        dst: &str,
+        opts: &FileCopyOptions,
+    ) -> Result<(), IPFSErrorKind> {
+        self.copy_to(&format!("/ipfs/{}", cid), dst, opts)
+    }
+
+    // Compares two trees, each an MFS path, a CID or an `/ipfs/` path.
+    // Subtrees with the same CID on both sides are skipped without being
+    // listed.
+    pub fn diff(&self, before: &str, after: &str) -> Result<Vec<TreeChange>, IPFSErrorKind> {
+        let mut changes = Vec::new();
+        self.diff_dirs(
+            &content_path(before),
+            &content_path(after),
+            "",
+            1,
+            &mut changes,
+        )?;
+        Ok(changes)
+    }
+
+    fn diff_dirs(
+        &self,
+        before: &str,
+        after: &str,
+        prefix: &str,
+        depth: usize,
+        changes: &mut Vec<TreeChange>,
+    ) -> Result<(), IPFSErrorKind> {
+        let mut entries: BTreeMap<String, (Option<TreeEntry>, Option<TreeEntry>)> = BTreeMap::new();
+        for entry in list_dir(self, before, depth)? {
+            let name = entry.name.clone();
+            entries.entry(name).or_default().0 = Some(entry);
+        }
+        for entry in list_dir(self, after, depth)? {
+            let name = entry.name.clone();
+            entries.entry(name).or_default().1 = Some(entry);
+        }
+        for (name, pair) in entries {
+            let path = format!("{}{}", prefix, name);
+            let kind = match &pair {
+                (Some(b), Some(a)) if b.hash.is_some() && b.hash == a.hash => continue,
+                (Some(b), Some(a)) if b.is_dir && a.is_dir => {
+                    let prefix = format!("{}/", path);
+                    self.diff_dirs(&b.path, &a.path, &prefix, depth + 1, changes)?;
+                    continue;
+                }
+                (Some(_), Some(_)) => TreeChangeKind::Modified,
+                (Some(_), None) => TreeChangeKind::Removed,
+                (None, _) => TreeChangeKind::Added,
+            };
+            let (before, after) = pair;
+            changes.push(TreeChange {
+                path,
+                kind,
+                before,
+                after,
+            });
+        }
+        Ok(())
+    }
+}
+
+// Bare CIDs are walked as `/ipfs/<cid>`.
+fn content_path(path: &str) -> String {
+    match path.parse::<Cid>() {
+        Ok(cid) => format!("/ipfs/{}", cid),
+        Err(_) => path.to_string(),
+    }
+}
+
+fn join(dir: &str, name: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), name)
+}
+
+fn list_dir<T: IpfsTransport>(
+    client: &IpfsClient<T>,
+    path: &str,
+    depth: usize,
+) -> Result<Vec<TreeEntry>, IPFSErrorKind> {
+    if path.starts_with("/ipfs/") || path.starts_with("/ipns/") {
+        return list_content(client, path, depth);
+    }
+    let opts = FileListOptions {
+        long: true,
+        unsorted: false,
+    };
+    Ok(client
+        .list(Some(path), &opts)?
+        .into_iter()
+        .map(|file| TreeEntry {
+            path: join(path, file.name()),
+            name: file.name().to_string(),
+            depth,
+            is_dir: file.is_dir(),
+            size: file.size(),
+            hash: file.hash().cloned(),
+        })
+        .collect())
+}
+
+fn list_content<T: IpfsTransport>(
+    client: &IpfsClient<T>,
+    path: &str,
+    depth: usize,
+) -> Result<Vec<TreeEntry>, IPFSErrorKind> {
+    let mut request = IpfsRequest::new("ls");
+    request.add_arg("arg", path);
+    request.add_arg("resolve-type", "true");
+    request.add_arg("size", "true");
+    let parsed = client.call_json(&request)?;
+    let links = &parsed["Objects"][0]["Links"];
+    if !links.is_array() && !links.is_null() {
+        return Err(IPFSErrorKind::InvalidParameter);
+    }
+    Ok(links
+        .members()
+        .filter_map(|link| content_entry(link, path, depth))
+        .collect())
+}
+
+fn content_entry(link: &JsonValue, dir: &str, depth: usize) -> Option<TreeEntry> {
+    let name = link["Name"].as_str()?;
+    // unixfs types: 1 is a directory, 5 a sharded directory
+    let is_dir = matches!(link["Type"].as_u64()?, 1 | 5);
+    Some(TreeEntry {
+        path: join(dir, name),
+        name: name.to_string(),
+        depth,
+        is_dir,
+        size: link["Size"].as_u64().unwrap_or(0),
+        hash: parse_cid(&link["Hash"]),
+    })
+}
+
+pub fn ipfs_tree_size(path: &str) -> Result<u64, IPFSErrorKind> {
+    IpfsClient::new().tree_size(path)
+}
+
+pub fn ipfs_tree_diff(before: &str, after: &str) -> Result<Vec<TreeChange>, IPFSErrorKind> {
+    IpfsClient::new().diff(before, after)
+}
@@ -0,0 +1,545 @@
+// The IPLD data model with local dag-json and dag-cbor codecs. Encoding is
+// canonical (sorted map keys, shortest integer forms, 64-bit floats), so the
+// CID computed by `Ipld::cid` matches the one the IPFS node stores the node
+// under with the same codec and sha2-256.
+
+use std::{collections::BTreeMap, fmt};
+
+use json::JsonValue;
+
+use crate::{Cid, IpldErrorKind, Multihash, CODEC_DAG_CBOR, CODEC_DAG_JSON};
+
+// Nesting deeper than this is rejected when decoding.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Ipld {
+    Null,
+    Bool(bool),
+    // dag-cbor integers range from -2^64 to 2^64 - 1
+    Integer(i128),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<Ipld>),
+    Map(BTreeMap<String, Ipld>),
+    Link(Cid),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DagCodec {
+    DagJson,
+    DagCbor,
+}
+
+impl DagCodec {
+    // The name the IPFS api uses, e.g. for `dag put --store-codec`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DagCodec::DagJson => "dag-json",
+            DagCodec::DagCbor => "dag-cbor",
+        }
+    }
+
+    pub fn code(&self) -> u64 {
+        match self {
+            DagCodec::DagJson => CODEC_DAG_JSON,
+            DagCodec::DagCbor => CODEC_DAG_CBOR,
+        }
+    }
+}
+
+impl Ipld {
+    pub fn get(&self, key: &str) -> Option<&Ipld> {
+        match self {
+            Ipld::Map(map) => map.get(key),
+            Ipld::List(list) => list.get(key.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    // Follows a `/`-separated path of map keys and list indexes within
+    // this node; links are not followed.
+    pub fn get_path(&self, path: &str) -> Option<&Ipld> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .try_fold(self, |node, segment| node.get(segment))
+    }
+
+    pub fn as_link(&self) -> Option<&Cid> {
+        match self {
+            Ipld::Link(cid) => Some(cid),
+            _ => None,
+        }
+    }
+
+    // Every link in this node, depth first.
+    pub fn links(&self) -> Vec<&Cid> {
+        let mut links = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                Ipld::Link(cid) => links.push(cid),
+                Ipld::List(list) => stack.extend(list.iter().rev()),
+                Ipld::Map(map) => stack.extend(map.values().rev()),
+                _ => {}
+            }
+        }
+        links
+    }
+
+    pub fn encode(&self, codec: DagCodec) -> Result<Vec<u8>, IpldErrorKind> {
+        match codec {
+            DagCodec::DagJson => encode_dag_json(self),
+            DagCodec::DagCbor => encode_dag_cbor(self),
+        }
+    }
+
+    pub fn decode(codec: DagCodec, bytes: &[u8]) -> Result<Ipld, IpldErrorKind> {
+        match codec {
+            DagCodec::DagJson => decode_dag_json(bytes),
+            DagCodec::DagCbor => decode_dag_cbor(bytes),
+        }
+    }
+
+    // CIDv1 of the node encoded with `codec`, hashed with sha2-256.
+    pub fn cid(&self, codec: DagCodec) -> Result<Cid, IpldErrorKind> {
+        let bytes = self.encode(codec)?;
+        Ok(Cid::new_v1(codec.code(), Multihash::sha2_256(&bytes)))
+    }
+}
+
+impl fmt::Display for Ipld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match encode_dag_json(self) {
+            Ok(bytes) => f.write_str(&String::from_utf8_lossy(&bytes)),
+            Err(_) => write!(f, "{:?}", self),
+        }
+    }
+}
+
+macro_rules! ipld_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Ipld {
+                fn from(value: $ty) -> Ipld {
+                    Ipld::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+ipld_from! {
+    bool => Bool,
+    i32 => Integer,
+    i64 => Integer,
+    u32 => Integer,
+    u64 => Integer,
+    f64 => Float,
+    String => String,
+    &str => String,
+    Vec<u8> => Bytes,
+    Vec<Ipld> => List,
+    BTreeMap<String, Ipld> => Map,
+    Cid => Link,
+}
+
+pub fn encode_dag_json(node: &Ipld) -> Result<Vec<u8>, IpldErrorKind> {
+    let mut out = Vec::new();
+    write_json(node, &mut out)?;
+    Ok(out)
+}
+
+fn write_json(node: &Ipld, out: &mut Vec<u8>) -> Result<(), IpldErrorKind> {
+    match node {
+        Ipld::Null => out.extend_from_slice(b"null"),
+        Ipld::Bool(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
+        Ipld::Integer(i) => out.extend_from_slice(i.to_string().as_bytes()),
+        Ipld::Float(f) => {
+            if !f.is_finite() {
+                return Err(IpldErrorKind::UnsupportedValue(f.to_string()));
+            }
+            // `{:?}` keeps a fraction or exponent, so it reads back as a float
+            out.extend_from_slice(format!("{:?}", f).as_bytes());
+        }
+        Ipld::String(s) => write_json_string(s, out),
+        Ipld::Bytes(bytes) => {
+            out.extend_from_slice(br#"{"/":{"bytes":""#);
+            out.extend_from_slice(base64_encode(bytes).as_bytes());
+            out.extend_from_slice(br#""}}"#);
+        }
+        Ipld::List(list) => {
+            out.push(b'[');
+            for (i, item) in list.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_json(item, out)?;
+            }
+            out.push(b']');
+        }
+        Ipld::Map(map) => {
+            // a map with only a "/" key is how dag-json writes links and
+            // bytes, so it would not read back as a map
+            if map.len() == 1 && map.contains_key("/") {
+                return Err(IpldErrorKind::UnsupportedValue(
+                    "map with a single \"/\" key".to_string(),
+                ));
+            }
+            // `BTreeMap` iterates in bytewise key order, as dag-json wants
+            out.push(b'{');
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_json_string(key, out);
+                out.push(b':');
+                write_json(value, out)?;
+            }
+            out.push(b'}');
+        }
+        Ipld::Link(cid) => {
+            out.extend_from_slice(br#"{"/":""#);
+            out.extend_from_slice(cid.to_string().as_bytes());
+            out.extend_from_slice(br#""}"#);
+        }
+    }
+    Ok(())
+}
+
+fn write_json_string(s: &str, out: &mut Vec<u8>) {
+    out.push(b'"');
+    for c in s.chars() {
+        match c {
+            '"' => out.extend_from_slice(b"\\\""),
+            '\\' => out.extend_from_slice(b"\\\\"),
+            '\n' => out.extend_from_slice(b"\\n"),
+            '\r' => out.extend_from_slice(b"\\r"),
+            '\t' => out.extend_from_slice(b"\\t"),
+            '\u{08}' => out.extend_from_slice(b"\\b"),
+            '\u{0c}' => out.extend_from_slice(b"\\f"),
+            c if (c as u32) < 0x20 => {
+                out.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => {
+                let mut buf = [0; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    out.push(b'"');
+}
+
+pub fn decode_dag_json(bytes: &[u8]) -> Result<Ipld, IpldErrorKind> {
+    let text =
+        std::str::from_utf8(bytes).map_err(|e| IpldErrorKind::InvalidDagJson(e.to_string()))?;
+    let value = json::parse(text).map_err(|e| IpldErrorKind::InvalidDagJson(e.to_string()))?;
+    from_json(&value, 0)
+}
+
+fn from_json(value: &JsonValue, depth: usize) -> Result<Ipld, IpldErrorKind> {
+    if depth > MAX_DEPTH {
+        return Err(IpldErrorKind::InvalidDagJson(
+            "nesting too deep".to_string(),
+        ));
+    }
+    Ok(match value {
+        JsonValue::Null => Ipld::Null,
+        JsonValue::Boolean(b) => Ipld::Bool(*b),
+        JsonValue::Short(s) => Ipld::String(s.to_string()),
+        JsonValue::String(s) => Ipld::String(s.clone()),
+        JsonValue::Number(n) => {
+            let (positive, mantissa, exponent) = n.as_parts();
+            if exponent == 0 {
+                let i = mantissa as i128;
+                Ipld::Integer(if positive { i } else { -i })
+            } else {
+                Ipld::Float((*n).into())
+            }
+        }
+        JsonValue::Array(items) => Ipld::List(
+            items
+                .iter()
+                .map(|item| from_json(item, depth + 1))
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonValue::Object(object) => {
+            // `{"/": "<cid>"}` is a link and `{"/": {"bytes": "<base64>"}}`
+            // bytes
+            if object.len() == 1 {
+                match object.get("/") {
+                    Some(JsonValue::Short(_) | JsonValue::String(_)) => {
+                        let cid = value["/"].as_str().unwrap_or_default();
+                        return cid
+                            .parse()
+                            .map(Ipld::Link)
+                            .map_err(IpldErrorKind::InvalidLink);
+                    }
+                    Some(inner) if inner.len() == 1 && inner["bytes"].is_string() => {
+                        let encoded = inner["bytes"].as_str().unwrap_or_default();
+                        return base64_decode(encoded).map(Ipld::Bytes).ok_or_else(|| {
+                            IpldErrorKind::InvalidDagJson("invalid base64 bytes".to_string())
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            let mut map = BTreeMap::new();
+            for (key, value) in object.iter() {
+                map.insert(key.to_string(), from_json(value, depth + 1)?);
+            }
+            Ipld::Map(map)
+        }
+    })
+}
+
+pub fn encode_dag_cbor(node: &Ipld) -> Result<Vec<u8>, IpldErrorKind> {
+    let mut out = Vec::new();
+    write_cbor(node, &mut out)?;
+    Ok(out)
+}
+
+// CBOR major types.
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+// The CBOR tag of CIDs.
+const CID_TAG: u64 = 42;
+
+fn write_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+fn write_cbor(node: &Ipld, out: &mut Vec<u8>) -> Result<(), IpldErrorKind> {
+    match node {
+        Ipld::Null => out.push(0xf6),
+        Ipld::Bool(b) => out.push(if *b { 0xf5 } else { 0xf4 }),
+        Ipld::Integer(i) => {
+            let (major, value) = if *i >= 0 {
+                (UNSIGNED, u64::try_from(*i))
+            } else {
+                (NEGATIVE, u64::try_from(-1 - *i))
+            };
+            let value = value.map_err(|_| IpldErrorKind::UnsupportedValue(i.to_string()))?;
+            write_head(out, major, value);
+        }
+        Ipld::Float(f) => {
+            if !f.is_finite() {
+                return Err(IpldErrorKind::UnsupportedValue(f.to_string()));
+            }
+            out.push(0xfb);
+            out.extend_from_slice(&f.to_be_bytes());
+        }
+        Ipld::String(s) => {
+            write_head(out, TEXT, s.len() as u64);
+            out.extend_from_slice(s.as_bytes());
+        }
+        Ipld::Bytes(bytes) => {
+            write_head(out, BYTES, bytes.len() as u64);
+            out.extend_from_slice(bytes);
+        }
+        Ipld::List(list) => {
+            write_head(out, ARRAY, list.len() as u64);
+            for item in list {
+                write_cbor(item, out)?;
+            }
+        }
+        Ipld::Map(map) => {
+            write_head(out, MAP, map.len() as u64);
+            // dag-cbor sorts keys by length first, then bytewise
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then(a.cmp(b)));
+            for (key, value) in entries {
+                write_head(out, TEXT, key.len() as u64);
+                out.extend_from_slice(key.as_bytes());
+                write_cbor(value, out)?;
+            }
+        }
+        Ipld::Link(cid) => {
+            write_head(out, TAG, CID_TAG);
+            // the identity multibase prefix
+            let bytes = cid.to_bytes();
+            write_head(out, BYTES, bytes.len() as u64 + 1);
+            out.push(0);
+            out.extend_from_slice(&bytes);
+        }
+    }
+    Ok(())
+}
+
+pub fn decode_dag_cbor(bytes: &[u8]) -> Result<Ipld, IpldErrorKind> {
+    let mut decoder = CborDecoder { bytes, pos: 0 };
+    let node = decoder.read(0)?;
+    if decoder.pos != bytes.len() {
+        return Err(decoder.error("trailing bytes"));
+    }
+    Ok(node)
+}
+
+struct CborDecoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl CborDecoder<'_> {
+    fn error(&self, message: &str) -> IpldErrorKind {
+        IpldErrorKind::InvalidDagCbor(format!("{} at byte {}", message, self.pos))
+    }
+
+    fn take(&mut self, n: usize) -> Result<&[u8], IpldErrorKind> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn head(&mut self) -> Result<(u8, u8, u64), IpldErrorKind> {
+        let first = self.take(1)?[0];
+        let (major, info) = (first >> 5, first & 0x1f);
+        let value = match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+            _ => return Err(self.error("indefinite lengths are not allowed")),
+        };
+        Ok((major, info, value))
+    }
+
+    fn len(&self, value: u64) -> Result<usize, IpldErrorKind> {
+        usize::try_from(value)
+            .ok()
+            .filter(|len| *len <= self.bytes.len() - self.pos)
+            .ok_or_else(|| self.error("length exceeds input"))
+    }
+
+    fn string(&mut self, len: u64) -> Result<String, IpldErrorKind> {
+        let len = self.len(len)?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| self.error("invalid utf-8 string"))
+    }
+
+    fn read(&mut self, depth: usize) -> Result<Ipld, IpldErrorKind> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        let (major, info, value) = self.head()?;
+        Ok(match major {
+            UNSIGNED => Ipld::Integer(value as i128),
+            NEGATIVE => Ipld::Integer(-1 - value as i128),
+            BYTES => {
+                let len = self.len(value)?;
+                Ipld::Bytes(self.take(len)?.to_vec())
+            }
+            TEXT => Ipld::String(self.string(value)?),
+            ARRAY => {
+                let len = self.len(value)?;
+                let mut list = Vec::with_capacity(len);
+                for _ in 0..len {
+                    list.push(self.read(depth + 1)?);
+                }
+                Ipld::List(list)
+            }
+            MAP => {
+                let len = self.len(value)?;
+                let mut map = BTreeMap::new();
+                for _ in 0..len {
+                    let (major, _, key_len) = self.head()?;
+                    if major != TEXT {
+                        return Err(self.error("map keys must be strings"));
+                    }
+                    let key = self.string(key_len)?;
+                    let value = self.read(depth + 1)?;
+                    if map.insert(key, value).is_some() {
+                        return Err(self.error("duplicate map key"));
+                    }
+                }
+                Ipld::Map(map)
+            }
+            TAG if value == CID_TAG => {
+                let (major, _, len) = self.head()?;
+                if major != BYTES {
+                    return Err(self.error("a CID must be a byte string"));
+                }
+                let len = self.len(len)?;
+                let bytes = self.take(len)?;
+                match bytes.split_first() {
+                    Some((0, cid)) => {
+                        Ipld::Link(Cid::from_bytes(cid).map_err(IpldErrorKind::InvalidLink)?)
+                    }
+                    _ => return Err(self.error("a CID must start with the identity multibase")),
+                }
+            }
+            TAG => return Err(self.error("only the CID tag 42 is allowed")),
+            _ => match info {
+                20 => Ipld::Bool(false),
+                21 => Ipld::Bool(true),
+                22 => Ipld::Null,
+                27 => Ipld::Float(f64::from_bits(value)),
+                _ => return Err(self.error("only 64-bit floats, booleans and null are allowed")),
+            },
+        })
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// Standard alphabet without padding, as dag-json writes bytes.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+// Accepts padded and unpadded input.
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
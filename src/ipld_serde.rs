@@ -0,0 +1,641 @@
+// Maps Rust values to `Ipld` and back with serde, so a struct can be stored
+// as a dag-json or dag-cbor node. `Cid` fields become links; byte fields need
+// `#[serde(with = "serde_bytes")]` or similar to become bytes rather than
+// lists of integers.
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::{
+    de::{
+        self, value::MapDeserializer, value::SeqDeserializer, DeserializeOwned, EnumAccess,
+        IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{Cid, Ipld, IpldErrorKind};
+
+// Newtype name that marks a CID, so `to_ipld` can tell it from a string.
+const CID_NAME: &str = "$__ipld_cid";
+
+pub fn to_ipld<T: Serialize + ?Sized>(value: &T) -> Result<Ipld, IpldErrorKind> {
+    value.serialize(IpldSerializer)
+}
+
+pub fn from_ipld<T: DeserializeOwned>(node: Ipld) -> Result<T, IpldErrorKind> {
+    T::deserialize(node)
+}
+
+impl ser::Error for IpldErrorKind {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        IpldErrorKind::Serde(msg.to_string())
+    }
+}
+
+impl de::Error for IpldErrorKind {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        IpldErrorKind::Serde(msg.to_string())
+    }
+}
+
+// Other serializers, e.g. serde_json, see the CID string.
+impl Serialize for Cid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(CID_NAME, &self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(CID_NAME, CidVisitor)
+    }
+}
+
+struct CidVisitor;
+
+impl<'de> Visitor<'de> for CidVisitor {
+    type Value = Cid;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a CID")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Cid, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Cid, E> {
+        Cid::from_bytes(v).map_err(E::custom)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<Cid, D::Error> {
+        d.deserialize_any(CidVisitor)
+    }
+}
+
+impl Serialize for Ipld {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Ipld::Null => serializer.serialize_unit(),
+            Ipld::Bool(b) => serializer.serialize_bool(*b),
+            Ipld::Integer(i) => match i64::try_from(*i) {
+                Ok(i) => serializer.serialize_i64(i),
+                Err(_) => serializer.serialize_i128(*i),
+            },
+            Ipld::Float(f) => serializer.serialize_f64(*f),
+            Ipld::String(s) => serializer.serialize_str(s),
+            Ipld::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Ipld::List(list) => serializer.collect_seq(list),
+            Ipld::Map(map) => serializer.collect_map(map),
+            Ipld::Link(cid) => cid.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Ipld {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(IpldVisitor)
+    }
+}
+
+struct IpldVisitor;
+
+impl<'de> Visitor<'de> for IpldVisitor {
+    type Value = Ipld;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an IPLD value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Ipld, E> {
+        Ok(Ipld::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Ipld, E> {
+        Ok(Ipld::Integer(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Ipld, E> {
+        Ok(Ipld::Integer(v.into()))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Ipld, E> {
+        Ok(Ipld::Integer(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Ipld, E> {
+        Ok(Ipld::Float(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Ipld, E> {
+        Ok(Ipld::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Ipld, E> {
+        Ok(Ipld::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Ipld, E> {
+        Ok(Ipld::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Ipld, E> {
+        Ok(Ipld::Bytes(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Ipld, E> {
+        Ok(Ipld::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Ipld, E> {
+        Ok(Ipld::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Ipld, D::Error> {
+        Ipld::deserialize(d)
+    }
+
+    // only links are newtypes
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<Ipld, D::Error> {
+        d.deserialize_any(CidVisitor).map(Ipld::Link)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Ipld, A::Error> {
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            list.push(item);
+        }
+        Ok(Ipld::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Ipld, A::Error> {
+        let mut map = BTreeMap::new();
+        while let Some((key, value)) = access.next_entry()? {
+            map.insert(key, value);
+        }
+        Ok(Ipld::Map(map))
+    }
+}
+
+struct IpldSerializer;
+
+fn integer<T: TryInto<i128>>(v: T) -> Result<Ipld, IpldErrorKind> {
+    v.try_into()
+        .map(Ipld::Integer)
+        .map_err(|_| IpldErrorKind::UnsupportedValue("integer out of range".to_string()))
+}
+
+fn variant(name: &str, value: Ipld) -> Ipld {
+    Ipld::Map(BTreeMap::from([(name.to_string(), value)]))
+}
+
+impl Serializer for IpldSerializer {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Ipld, IpldErrorKind> {
+        integer(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Ipld, IpldErrorKind> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Ipld, IpldErrorKind> {
+        Ok(Ipld::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Ipld, IpldErrorKind> {
+        match (name, value.serialize(self)?) {
+            (CID_NAME, Ipld::String(cid)) => cid
+                .parse()
+                .map(Ipld::Link)
+                .map_err(IpldErrorKind::InvalidLink),
+            (_, node) => Ok(node),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Ipld, IpldErrorKind> {
+        Ok(variant(name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, IpldErrorKind> {
+        Ok(SerializeList {
+            variant: None,
+            list: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, IpldErrorKind> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, IpldErrorKind> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, IpldErrorKind> {
+        Ok(SerializeList {
+            variant: Some(variant),
+            list: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, IpldErrorKind> {
+        Ok(SerializeMap {
+            variant: None,
+            map: BTreeMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap, IpldErrorKind> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeMap, IpldErrorKind> {
+        Ok(SerializeMap {
+            variant: Some(variant),
+            map: BTreeMap::new(),
+            key: None,
+        })
+    }
+}
+
+struct SerializeList {
+    variant: Option<&'static str>,
+    list: Vec<Ipld>,
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IpldErrorKind> {
+        self.list.push(value.serialize(IpldSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Ipld, IpldErrorKind> {
+        let list = Ipld::List(self.list);
+        Ok(match self.variant {
+            Some(name) => variant(name, list),
+            None => list,
+        })
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IpldErrorKind> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Ipld, IpldErrorKind> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IpldErrorKind> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Ipld, IpldErrorKind> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IpldErrorKind> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Ipld, IpldErrorKind> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeMap {
+    variant: Option<&'static str>,
+    map: BTreeMap<String, Ipld>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), IpldErrorKind> {
+        match key.serialize(IpldSerializer)? {
+            Ipld::String(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(IpldErrorKind::UnsupportedValue(
+                "map keys must be strings".to_string(),
+            )),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), IpldErrorKind> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| IpldErrorKind::Serde("value without a key".to_string()))?;
+        self.map.insert(key, value.serialize(IpldSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Ipld, IpldErrorKind> {
+        let map = Ipld::Map(self.map);
+        Ok(match self.variant {
+            Some(name) => variant(name, map),
+            None => map,
+        })
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), IpldErrorKind> {
+        self.map
+            .insert(key.to_string(), value.serialize(IpldSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Ipld, IpldErrorKind> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = Ipld;
+    type Error = IpldErrorKind;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), IpldErrorKind> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Ipld, IpldErrorKind> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, IpldErrorKind> for Ipld {
+    type Deserializer = Ipld;
+
+    fn into_deserializer(self) -> Ipld {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Ipld {
+    type Error = IpldErrorKind;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IpldErrorKind> {
+        match self {
+            Ipld::Null => visitor.visit_unit(),
+            Ipld::Bool(b) => visitor.visit_bool(b),
+            Ipld::Integer(i) => {
+                if let Ok(i) = i64::try_from(i) {
+                    visitor.visit_i64(i)
+                } else if let Ok(u) = u64::try_from(i) {
+                    visitor.visit_u64(u)
+                } else {
+                    visitor.visit_i128(i)
+                }
+            }
+            Ipld::Float(f) => visitor.visit_f64(f),
+            Ipld::String(s) => visitor.visit_string(s),
+            Ipld::Bytes(bytes) => visitor.visit_byte_buf(bytes),
+            Ipld::List(list) => visitor.visit_seq(SeqDeserializer::new(list.into_iter())),
+            Ipld::Map(map) => visitor.visit_map(MapDeserializer::new(map.into_iter())),
+            // only `Cid` and `Ipld` accept a link
+            Ipld::Link(cid) => visitor.visit_newtype_struct(cid.to_string().into_deserializer()),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IpldErrorKind> {
+        match self {
+            Ipld::Null => visitor.visit_none(),
+            node => visitor.visit_some(node),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, IpldErrorKind> {
+        match self {
+            Ipld::Link(cid) if name == CID_NAME => visitor.visit_string(cid.to_string()),
+            node => visitor.visit_newtype_struct(node),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, IpldErrorKind> {
+        match self {
+            Ipld::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Ipld::Map(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().unwrap();
+                visitor.visit_enum(Enum { variant, value })
+            }
+            _ => Err(IpldErrorKind::Serde(
+                "an enum must be a string or a map with a single key".to_string(),
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct Enum {
+    variant: String,
+    value: Ipld,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = IpldErrorKind;
+    type Variant = Ipld;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Ipld), IpldErrorKind> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Ipld {
+    type Error = IpldErrorKind;
+
+    fn unit_variant(self) -> Result<(), IpldErrorKind> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, IpldErrorKind> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, IpldErrorKind> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, IpldErrorKind> {
+        self.deserialize_any(visitor)
+    }
+}
@@ -1,21 +1,90 @@
+#[cfg(feature = "s3")]
 mod awss3;
+#[cfg(feature = "s3")]
 mod awss3_host;
+pub mod capabilities;
+#[cfg(feature = "cgi")]
 mod cgi;
+#[cfg(feature = "cgi")]
 mod cgi_host;
+mod cid;
+#[cfg(feature = "http")]
+pub mod cookie;
+#[cfg(feature = "serde")]
+pub mod entry;
+pub mod env;
 mod error;
+mod host;
+#[cfg(feature = "http")]
 mod http;
+#[cfg(feature = "http-compat")]
+pub mod http_compat;
+#[cfg(feature = "http")]
 mod http_host;
+#[cfg(feature = "serde")]
+pub mod input;
+#[cfg(feature = "ipfs")]
 mod ipfs;
+#[cfg(feature = "ipfs")]
+mod ipfs_dag;
+#[cfg(feature = "ipfs")]
+mod ipfs_file;
+#[cfg(feature = "ipfs")]
 mod ipfs_host;
+#[cfg(feature = "ipfs")]
+mod ipfs_pin;
+#[cfg(feature = "ipfs")]
+mod ipfs_tree;
+mod ipld;
+#[cfg(feature = "serde")]
+mod ipld_serde;
 mod memory;
 mod memory_host;
+#[cfg(feature = "http")]
+pub mod oauth2;
+#[cfg(feature = "serde")]
+pub mod output;
+pub mod permissions;
+mod read_loop;
+mod secret;
+mod sha256;
+#[cfg(feature = "socket")]
 mod socket;
+#[cfg(feature = "socket")]
 mod socket_host;
+#[cfg(feature = "ipfs")]
+mod tar;
+mod unixfs;
+#[cfg(feature = "http")]
+mod url;
 
+#[cfg(feature = "s3")]
 pub use awss3::*;
+#[cfg(feature = "macros")]
+pub use blockless_sdk_macros::main;
+#[cfg(feature = "cgi")]
 pub use cgi::*;
+pub use cid::*;
 pub use error::*;
+pub use host::host_call_count;
+#[cfg(feature = "http")]
 pub use http::*;
+#[cfg(feature = "ipfs")]
 pub use ipfs::*;
+#[cfg(feature = "ipfs")]
+pub use ipfs_dag::*;
+#[cfg(feature = "ipfs")]
+pub use ipfs_file::*;
+#[cfg(feature = "ipfs")]
+pub use ipfs_pin::*;
+#[cfg(feature = "ipfs")]
+pub use ipfs_tree::*;
+pub use ipld::*;
+#[cfg(feature = "serde")]
+pub use ipld_serde::*;
 pub use memory::*;
+pub use read_loop::*;
+pub use secret::*;
+#[cfg(feature = "socket")]
 pub use socket::*;
+pub use unixfs::*;
@@ -1,21 +1,96 @@
+use std::io;
+
 use crate::memory_host::*;
 
+pub const MAX_STDIN_SIZE: usize = 256 * 1024 * 1024;
+
+const INITIAL_STDIN_SIZE: usize = 4096;
+
+// Copies the function input into `buf` and returns its full length. The
+// host starts from the beginning of the input on every call, so a return
+// value of `buf.len()` or more means the input didn't fit.
 pub fn read_stdin(buf: &mut [u8]) -> std::io::Result<u32> {
     let mut len = 0;
     let errno = unsafe { memory_read(buf.as_mut_ptr(), buf.len() as _, &mut len) };
     if errno == 0 {
         return Ok(len);
     }
-    let err = std::io::Error::from_raw_os_error(errno as i32);
+    let err = io::Error::from_raw_os_error(errno as i32);
     Err(err)
 }
 
-pub fn read_env_vars(buf: &mut [u8]) -> std::io::Result<u32> {
+// Reads the whole function input.
+pub fn read_stdin_to_end() -> io::Result<Vec<u8>> {
+    read_whole(INITIAL_STDIN_SIZE, MAX_STDIN_SIZE, read_stdin)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::FileTooLarge,
+            "input is larger than MAX_STDIN_SIZE",
+        )
+    })
+}
+
+// `memory_read` and `env_var_read` copy the whole payload into the buffer
+// on every call instead of streaming it, so retry with a bigger buffer
+// until the payload fits. Returns `None` when it is larger than `max`.
+pub(crate) fn read_whole(
+    initial: usize,
+    max: usize,
+    mut read: impl FnMut(&mut [u8]) -> io::Result<u32>,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut size = initial.min(max);
+    loop {
+        let mut buf = vec![0u8; size];
+        let len = read(&mut buf)? as usize;
+        if len < size {
+            buf.truncate(len);
+            return Ok(Some(buf));
+        }
+        if size >= max {
+            return Ok(None);
+        }
+        // a host that reports the full length lets us size the buffer at once
+        size = size.saturating_mul(2).max(len.saturating_add(1)).min(max);
+    }
+}
+
+pub fn read_stdin_to_string() -> io::Result<String> {
+    let buf = read_stdin_to_end()?;
+    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// `std::io::Read` over the function input, for use with `BufReader`,
+// `serde_json::from_reader` and the like. The input is read from the host
+// in full on the first read, then served from memory.
+#[derive(Default)]
+pub struct StdinReader {
+    input: Option<io::Cursor<Vec<u8>>>,
+}
+
+impl StdinReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl io::Read for StdinReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let input = match &mut self.input {
+            Some(input) => input,
+            None => self.input.insert(io::Cursor::new(read_stdin_to_end()?)),
+        };
+        input.read(buf)
+    }
+}
+
+pub fn read_env_vars(buf: &mut [u8]) -> io::Result<u32> {
     let mut len = 0;
     let errno = unsafe { env_var_read(buf.as_mut_ptr(), buf.len() as _, &mut len) };
     if errno == 0 {
         return Ok(len);
     }
-    let err = std::io::Error::from_raw_os_error(errno as i32);
+    let err = io::Error::from_raw_os_error(errno as i32);
     Err(err)
 }
@@ -0,0 +1,263 @@
+use std::time::{Duration, Instant};
+
+use json::JsonValue;
+
+use crate::{
+    BlocklessHttp, CodeStatus, HttpErrorKind, HttpOptions, OAuth2ErrorKind, Secret, Zeroize,
+};
+
+pub const DEFAULT_EXPIRY_SKEW: Duration = Duration::from_secs(30);
+
+// Performs the POST to the token endpoint. Implement it to serve canned
+// responses when running without a blockless runtime.
+pub trait TokenTransport {
+    fn post_form(&self, url: &str, form: &str) -> Result<(CodeStatus, Vec<u8>), HttpErrorKind>;
+}
+
+pub struct HttpTransport {
+    pub connect_timeout: u32,
+    pub read_timeout: u32,
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        HttpTransport {
+            connect_timeout: 30,
+            read_timeout: 10,
+        }
+    }
+}
+
+impl TokenTransport for HttpTransport {
+    fn post_form(&self, url: &str, form: &str) -> Result<(CodeStatus, Vec<u8>), HttpErrorKind> {
+        let mut opts = HttpOptions::new("POST", self.connect_timeout, self.read_timeout);
+        opts.set_header("Content-Type", "application/x-www-form-urlencoded");
+        opts.set_header("Accept", "application/json");
+        opts.body = Some(form.to_string());
+        let http = BlocklessHttp::open(url, &opts)?;
+        let body = http.get_all_body();
+        let code = http.get_code();
+        http.close();
+        Ok((code, body?))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub access_token: String,
+    pub token_type: String,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    pub expires_at: Option<Instant>,
+}
+
+impl Token {
+    fn parse(body: &[u8], now: Instant) -> Result<Self, OAuth2ErrorKind> {
+        let body = std::str::from_utf8(body).map_err(|_| OAuth2ErrorKind::Utf8Error)?;
+        let json = json::parse(body).map_err(|_| OAuth2ErrorKind::InvalidResponse)?;
+        let access_token = json["access_token"]
+            .as_str()
+            .ok_or(OAuth2ErrorKind::InvalidResponse)?
+            .to_string();
+        let token_type = json["token_type"].as_str().unwrap_or("Bearer").to_string();
+        // some providers send expires_in as a string
+        let expires_in = json["expires_in"].as_u64().or_else(|| {
+            json["expires_in"]
+                .as_str()
+                .and_then(|s| s.parse::<u64>().ok())
+        });
+        Ok(Token {
+            access_token,
+            token_type,
+            refresh_token: json["refresh_token"].as_str().map(|s| s.to_string()),
+            scope: json["scope"].as_str().map(|s| s.to_string()),
+            // an expiry too far out to represent is treated as none
+            expires_at: expires_in.and_then(|secs| now.checked_add(Duration::from_secs(secs))),
+        })
+    }
+
+    pub fn is_expired(&self, skew: Duration) -> bool {
+        match self.expires_at {
+            Some(expires_at) => Instant::now()
+                .checked_add(skew)
+                .is_none_or(|deadline| deadline >= expires_at),
+            None => false,
+        }
+    }
+
+    pub fn authorization(&self) -> String {
+        format!("Bearer {}", self.access_token)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OAuth2Config {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: Option<Secret<String>>,
+    pub scopes: Vec<String>,
+}
+
+impl OAuth2Config {
+    pub fn new(token_url: &str, client_id: &str) -> Self {
+        OAuth2Config {
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret: None,
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn set_client_secret(&mut self, client_secret: &str) {
+        self.client_secret = Some(Secret::from(client_secret.to_string()));
+    }
+
+    pub fn add_scope(&mut self, scope: &str) {
+        self.scopes.push(scope.into());
+    }
+}
+
+pub struct OAuth2Client<T: TokenTransport = HttpTransport> {
+    config: OAuth2Config,
+    transport: T,
+    token: Option<Token>,
+    refresh_token: Option<String>,
+    skew: Duration,
+}
+
+impl OAuth2Client<HttpTransport> {
+    pub fn new(config: OAuth2Config) -> Self {
+        Self::with_transport(config, HttpTransport::default())
+    }
+}
+
+impl<T: TokenTransport> OAuth2Client<T> {
+    pub fn with_transport(config: OAuth2Config, transport: T) -> Self {
+        OAuth2Client {
+            config,
+            transport,
+            token: None,
+            refresh_token: None,
+            skew: DEFAULT_EXPIRY_SKEW,
+        }
+    }
+
+    pub fn set_expiry_skew(&mut self, skew: Duration) {
+        self.skew = skew;
+    }
+
+    // Switches the client to the refresh-token grant.
+    pub fn set_refresh_token(&mut self, refresh_token: &str) {
+        self.refresh_token = Some(refresh_token.into());
+    }
+
+    pub fn invalidate(&mut self) {
+        self.token = None;
+    }
+
+    pub fn client_credentials(&mut self) -> Result<&Token, OAuth2ErrorKind> {
+        let mut form = vec![("grant_type", "client_credentials".to_string())];
+        let scope = self.config.scopes.join(" ");
+        if !scope.is_empty() {
+            form.push(("scope", scope));
+        }
+        self.request_token(form)
+    }
+
+    pub fn refresh(&mut self) -> Result<&Token, OAuth2ErrorKind> {
+        let refresh_token = self
+            .refresh_token
+            .clone()
+            .ok_or(OAuth2ErrorKind::MissingRefreshToken)?;
+        self.request_token(vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token),
+        ])
+    }
+
+    // Returns the cached token, fetching a new one when there is none or it
+    // expires within the skew margin.
+    pub fn token(&mut self) -> Result<&Token, OAuth2ErrorKind> {
+        let valid = matches!(&self.token, Some(token) if !token.is_expired(self.skew));
+        if !valid {
+            if self.refresh_token.is_some() {
+                self.refresh()?;
+            } else {
+                self.client_credentials()?;
+            }
+        }
+        self.token.as_ref().ok_or(OAuth2ErrorKind::InvalidResponse)
+    }
+
+    pub fn authorize(&mut self, opts: &mut HttpOptions) -> Result<(), OAuth2ErrorKind> {
+        let authorization = self.token()?.authorization();
+        opts.set_header("Authorization", &authorization);
+        Ok(())
+    }
+
+    pub fn open(
+        &mut self,
+        url: &str,
+        opts: &HttpOptions,
+    ) -> Result<BlocklessHttp, OAuth2ErrorKind> {
+        let mut opts = opts.clone();
+        self.authorize(&mut opts)?;
+        Ok(BlocklessHttp::open(url, &opts)?)
+    }
+
+    fn request_token(&mut self, mut form: Vec<(&str, String)>) -> Result<&Token, OAuth2ErrorKind> {
+        form.push(("client_id", self.config.client_id.clone()));
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.expose_secret().clone()));
+        }
+        // the form carries the client secret, so it is wiped once sent
+        let encoded = Secret::new(form_urlencode(&form));
+        form.iter_mut().for_each(|(_, value)| value.zeroize());
+        let now = Instant::now();
+        let (status, body) = self
+            .transport
+            .post_form(&self.config.token_url, encoded.expose_secret())?;
+        if !(200..300).contains(&status) {
+            return Err(token_error(status, &body));
+        }
+        let token = Token::parse(&body, now)?;
+        if let Some(refresh_token) = &token.refresh_token {
+            self.refresh_token = Some(refresh_token.clone());
+        }
+        Ok(self.token.insert(token))
+    }
+}
+
+fn token_error(status: u32, body: &[u8]) -> OAuth2ErrorKind {
+    let json = std::str::from_utf8(body)
+        .ok()
+        .and_then(|s| json::parse(s).ok())
+        .unwrap_or(JsonValue::Null);
+    OAuth2ErrorKind::TokenRequestFailed {
+        status,
+        error: json["error"].as_str().map(|s| s.to_string()),
+        description: json["error_description"].as_str().map(|s| s.to_string()),
+    }
+}
+
+fn form_urlencode(pairs: &[(&str, String)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", form_escape(name), form_escape(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn form_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => {
+                escaped.push(b as char)
+            }
+            b' ' => escaped.push('+'),
+            _ => escaped.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::Write,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use crate::host_call_count;
+
+pub const EXIT_FAILURE: u8 = 1;
+
+pub const EXIT_INPUT_ERROR: u8 = 2;
+
+pub const EXIT_ENV_ERROR: u8 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Cbor,
+}
+
+// Writes the function result to stdout, or the writer given to
+// `set_writer`, as an envelope:
+//
+//     {"status":"ok","data":...,"error":null,"metadata":{"duration_ms":12,"host_calls":3}}
+//     {"status":"error","data":null,"error":{"message":"..."},"metadata":{...}}
+//
+// Create it first thing in `main` so the reported duration covers the whole
+// invocation.
+pub struct Output {
+    format: Format,
+    started: Instant,
+    metadata: BTreeMap<String, serde_json::Value>,
+    writer: Option<Box<dyn Write>>,
+}
+
+impl Output {
+    pub fn new(format: Format) -> Self {
+        Output {
+            format,
+            started: Instant::now(),
+            metadata: BTreeMap::new(),
+            writer: None,
+        }
+    }
+
+    // Writes the envelope to `writer` instead of stdout.
+    pub fn set_writer(&mut self, writer: impl Write + 'static) {
+        self.writer = Some(Box::new(writer));
+    }
+
+    pub fn set_metadata(&mut self, name: &str, value: impl Into<serde_json::Value>) {
+        self.metadata.insert(name.into(), value.into());
+    }
+
+    // Writes the envelope for `result` and returns the exit status to return
+    // from `main`: success, or `EXIT_FAILURE` when `result` is an error.
+    pub fn finish<T: Serialize, E: Display>(self, result: Result<T, E>) -> ExitCode {
+        match result {
+            Ok(data) => self.succeed(&data),
+            Err(e) => self.fail(e, EXIT_FAILURE),
+        }
+    }
+
+    pub fn succeed<T: Serialize>(mut self, data: &T) -> ExitCode {
+        let data = match serde_json::to_value(data) {
+            Ok(data) => data,
+            Err(e) => return self.fail(format!("Serialize output error: {}", e), EXIT_FAILURE),
+        };
+        let envelope = Envelope {
+            data: Some(data),
+            error: None,
+            metadata: self.metadata(),
+        };
+        let written = self.write(&envelope);
+        match written {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(_) => ExitCode::from(EXIT_FAILURE),
+        }
+    }
+
+    pub fn fail(mut self, error: impl Display, exit_code: u8) -> ExitCode {
+        let envelope = Envelope {
+            data: None,
+            // `{:#}` so a `BlocklessError` reports its whole chain
+            error: Some(format!("{:#}", error)),
+            metadata: self.metadata(),
+        };
+        // the exit status already reports a failure
+        let _ = self.write(&envelope);
+        ExitCode::from(exit_code)
+    }
+
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            duration: self.started.elapsed(),
+            host_calls: host_call_count(),
+            extra: self.metadata.clone(),
+        }
+    }
+
+    // Writes `envelope`, or an error envelope in its place when it can't be
+    // encoded, in which case the encoding error is returned.
+    fn write(&mut self, envelope: &Envelope) -> Result<(), String> {
+        let (bytes, result) = match self.encode(envelope) {
+            Ok(bytes) => (Ok(bytes), Ok(())),
+            Err(e) => {
+                let error = Envelope {
+                    data: None,
+                    error: Some(format!("Encode output error: {}", e)),
+                    metadata: self.metadata(),
+                };
+                // JSON when the format itself is what can't be encoded
+                let bytes = self.encode(&error).or_else(|_| encode_json(&error));
+                (bytes, Err(e))
+            }
+        };
+        let mut stdout;
+        let out: &mut dyn Write = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                stdout = std::io::stdout().lock();
+                &mut stdout
+            }
+        };
+        // nothing sensible is left to do when the output itself is gone
+        let _ = match bytes {
+            Ok(bytes) => out.write_all(&bytes),
+            Err(e) => writeln!(
+                out,
+                "{{\"status\":\"error\",\"data\":null,\"error\":{{\"message\":{}}}}}",
+                serde_json::Value::from(e)
+            ),
+        };
+        let _ = out.flush();
+        result
+    }
+
+    fn encode(&self, envelope: &Envelope) -> Result<Vec<u8>, String> {
+        match self.format {
+            Format::Json => encode_json(envelope),
+            Format::Cbor => encode_cbor(envelope),
+        }
+    }
+}
+
+fn encode_json(envelope: &Envelope) -> Result<Vec<u8>, String> {
+    let mut bytes = serde_json::to_vec(envelope).map_err(|e| e.to_string())?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+struct Envelope {
+    data: Option<serde_json::Value>,
+    error: Option<String>,
+    metadata: Metadata,
+}
+
+impl Serialize for Envelope {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(4))?;
+        let status = if self.error.is_some() { "error" } else { "ok" };
+        map.serialize_entry("status", status)?;
+        map.serialize_entry("data", &self.data)?;
+        map.serialize_entry(
+            "error",
+            &self.error.as_ref().map(|message| ErrorBody { message }),
+        )?;
+        map.serialize_entry("metadata", &self.metadata)?;
+        map.end()
+    }
+}
+
+struct ErrorBody<'a> {
+    message: &'a str,
+}
+
+impl Serialize for ErrorBody<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("message", self.message)?;
+        map.end()
+    }
+}
+
+struct Metadata {
+    duration: Duration,
+    host_calls: u64,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl Serialize for Metadata {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2 + self.extra.len()))?;
+        map.serialize_entry("duration_ms", &(self.duration.as_millis() as u64))?;
+        map.serialize_entry("host_calls", &self.host_calls)?;
+        for (name, value) in &self.extra {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "cbor")]
+fn encode_cbor(envelope: &Envelope) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(envelope, &mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "cbor"))]
+fn encode_cbor(_envelope: &Envelope) -> Result<Vec<u8>, String> {
+    Err("cbor output requires the cbor feature".to_string())
+}
@@ -0,0 +1,177 @@
+use std::sync::RwLock;
+
+// A permission the function needs from the runtime. `Display` gives the
+// form passed to the runtime as `--permission`:
+//
+//     https://api.example.com/v1/
+//     s3://my-bucket
+//     ipfs:///data
+//     cgi://ffmpeg
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Permission {
+    // Allows every url starting with the prefix.
+    Url(String),
+    S3Bucket(String),
+    // Allows the MFS path and everything below it.
+    IpfsPath(String),
+    CgiAlias(String),
+}
+
+impl Permission {
+    // Whether this permission, as declared, allows the requested one.
+    pub fn allows(&self, requested: &Permission) -> bool {
+        match (self, requested) {
+            (Self::Url(prefix), Self::Url(url)) => {
+                !has_dot_segment(url_path(prefix))
+                    && !has_dot_segment(url_path(url))
+                    && is_prefix(prefix, url, &['/', '?', '#'])
+            }
+            (Self::S3Bucket(bucket), Self::S3Bucket(requested)) => bucket == requested,
+            (Self::IpfsPath(prefix), Self::IpfsPath(path)) => {
+                !has_dot_segment(prefix)
+                    && !has_dot_segment(path)
+                    && is_prefix(prefix, path, &['/'])
+            }
+            (Self::CgiAlias(alias), Self::CgiAlias(requested)) => alias == requested,
+            _ => false,
+        }
+    }
+}
+
+// `prefix` matches `value` when it is equal to it, or when `value` continues
+// after it with one of the `separators`, so `https://a.com` does not allow
+// `https://a.com.evil.com`.
+fn is_prefix(prefix: &str, value: &str, separators: &[char]) -> bool {
+    let Some(rest) = value.strip_prefix(prefix) else {
+        return false;
+    };
+    rest.is_empty() || prefix.ends_with(separators) || rest.starts_with(separators)
+}
+
+// Whether a path has a `.` or `..` segment, also percent-encoded or after a
+// `\`, which urls treat like `/`. The host may resolve them, so
+// `/data/../secrets` must not match the prefix `/data`; such paths are never
+// allowed.
+fn has_dot_segment(path: &str) -> bool {
+    path.split(['/', '\\']).any(|segment| {
+        let segment = segment.to_ascii_lowercase().replace("%2e", ".");
+        segment == "." || segment == ".."
+    })
+}
+
+// A url without its query and fragment.
+fn url_path(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or_default()
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Url(prefix) => write!(f, "{}", prefix),
+            Self::S3Bucket(bucket) => write!(f, "s3://{}", bucket),
+            Self::IpfsPath(path) => write!(f, "ipfs://{}", path),
+            Self::CgiAlias(alias) => write!(f, "cgi://{}", alias),
+        }
+    }
+}
+
+// The permissions a function declares it needs. Once installed, requests
+// that are not covered fail with a `MissingPermission` error naming the
+// permission, before they reach the host:
+//
+//     let mut manifest = Manifest::new();
+//     manifest.allow_url("https://api.coingecko.com/api/v3/");
+//     manifest.allow_s3_bucket("prices");
+//     manifest.install();
+//
+// Without an installed manifest every request is passed to the host.
+#[derive(Clone, Debug, Default)]
+pub struct Manifest {
+    permissions: Vec<Permission>,
+}
+
+impl Manifest {
+    pub fn new() -> Self {
+        Manifest::default()
+    }
+
+    pub fn allow(&mut self, permission: Permission) {
+        if !self.permissions.contains(&permission) {
+            self.permissions.push(permission);
+        }
+    }
+
+    pub fn allow_url(&mut self, prefix: &str) {
+        self.allow(Permission::Url(prefix.to_string()));
+    }
+
+    pub fn allow_s3_bucket(&mut self, bucket: &str) {
+        self.allow(Permission::S3Bucket(bucket.to_string()));
+    }
+
+    pub fn allow_ipfs_path(&mut self, path: &str) {
+        self.allow(Permission::IpfsPath(path.to_string()));
+    }
+
+    pub fn allow_cgi_alias(&mut self, alias: &str) {
+        self.allow(Permission::CgiAlias(alias.to_string()));
+    }
+
+    pub fn allows(&self, requested: &Permission) -> bool {
+        self.permissions.iter().any(|p| p.allows(requested))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Permission> {
+        self.permissions.iter()
+    }
+
+    // The declaration for the deploy tooling:
+    //
+    //     {"permissions":["https://api.coingecko.com/api/v3/","s3://prices"]}
+    //
+    // Each entry is the value of one `--permission` flag.
+    pub fn to_json(&self) -> String {
+        let permissions = self
+            .permissions
+            .iter()
+            .map(|p| json::JsonValue::String(p.to_string()))
+            .collect::<Vec<_>>();
+        let mut obj = json::object::Object::new();
+        obj.insert("permissions", json::JsonValue::Array(permissions));
+        obj.dump()
+    }
+
+    // Checks every following host request against this manifest, replacing
+    // any manifest installed before.
+    pub fn install(self) {
+        *MANIFEST.write().unwrap_or_else(|e| e.into_inner()) = Some(self);
+    }
+}
+
+static MANIFEST: RwLock<Option<Manifest>> = RwLock::new(None);
+
+pub fn installed() -> Option<Manifest> {
+    MANIFEST.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn uninstall() {
+    *MANIFEST.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+// Whether the installed manifest allows `requested`; always true when none
+// is installed.
+pub fn is_allowed(requested: &Permission) -> bool {
+    match &*MANIFEST.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(manifest) => manifest.allows(requested),
+        None => true,
+    }
+}
+
+#[cfg(any(feature = "http", feature = "ipfs", feature = "s3", feature = "cgi"))]
+pub(crate) fn require(requested: Permission) -> Result<(), Permission> {
+    if is_allowed(&requested) {
+        Ok(())
+    } else {
+        Err(requested)
+    }
+}
@@ -0,0 +1,103 @@
+pub const DEFAULT_CHUNK_SIZE: usize = 4 * 1024;
+
+pub const DEFAULT_MAX_CHUNK_SIZE: usize = 1024 * 1024;
+
+pub const DEFAULT_MAX_PREALLOCATE: usize = 16 * 1024 * 1024;
+
+// Size of the stack buffer used to check for end of data once a buffer
+// pre-sized from a size hint is full, so a correct hint never reallocates.
+const PROBE_SIZE: usize = 64;
+
+// Drives the `read(handle, buf, len, &mut num)` host calls shared by all
+// extensions until the host reports end of data (`num == 0`).
+//
+// Reads go straight into the spare capacity of the destination `Vec`. Each
+// time the host fills a whole chunk the next chunk is doubled, up to
+// `max_chunk_size`. When the total size is known up front (`Content-Length`,
+// `files/stat`, ...) set it as size hint: the buffer is allocated once and
+// the body is usually read with a single host call.
+#[derive(Clone, Debug)]
+pub struct ReadLoop {
+    pub chunk_size: usize,
+    pub max_chunk_size: usize,
+    pub size_hint: Option<usize>,
+    pub max_preallocate: usize,
+}
+
+impl Default for ReadLoop {
+    fn default() -> Self {
+        ReadLoop {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            max_chunk_size: DEFAULT_MAX_CHUNK_SIZE,
+            size_hint: None,
+            max_preallocate: DEFAULT_MAX_PREALLOCATE,
+        }
+    }
+}
+
+impl ReadLoop {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_chunk_size(&mut self, chunk_size: usize, max_chunk_size: usize) {
+        self.chunk_size = chunk_size.max(1);
+        self.max_chunk_size = max_chunk_size.max(self.chunk_size);
+    }
+
+    // Hints above `max_preallocate` are clamped, a bogus size reported by a
+    // remote server must not be able to exhaust wasm memory on its own.
+    pub fn set_size_hint(&mut self, size_hint: u64) {
+        self.size_hint = Some(usize::try_from(size_hint).unwrap_or(usize::MAX));
+    }
+
+    // Appends everything `read` produces to `buf` and returns the number of
+    // bytes appended. `read` fills the given slice and returns how many bytes
+    // it wrote, `0` meaning end of data.
+    pub fn read_to_end<E, F>(&self, buf: &mut Vec<u8>, mut read: F) -> Result<usize, E>
+    where
+        F: FnMut(&mut [u8]) -> Result<usize, E>,
+    {
+        let start = buf.len();
+        let mut chunk = self.chunk_size.max(1);
+        let mut probed = false;
+        if let Some(hint) = self.size_hint {
+            let hint = hint.min(self.max_preallocate);
+            buf.reserve(hint);
+            chunk = chunk.max(hint);
+        }
+        loop {
+            if buf.len() == buf.capacity() {
+                if self.size_hint.is_some() && !probed {
+                    probed = true;
+                    let mut probe = [0u8; PROBE_SIZE];
+                    let num = read(&mut probe)?.min(PROBE_SIZE);
+                    if num == 0 {
+                        break;
+                    }
+                    buf.extend_from_slice(&probe[..num]);
+                    continue;
+                }
+                buf.reserve(chunk);
+            }
+            let len = buf.len();
+            let want = (buf.capacity() - len).min(chunk);
+            buf.resize(len + want, 0);
+            let num = match read(&mut buf[len..]) {
+                Ok(num) => num.min(want),
+                Err(e) => {
+                    buf.truncate(len);
+                    return Err(e);
+                }
+            };
+            buf.truncate(len + num);
+            if num == 0 {
+                break;
+            }
+            if num == want {
+                chunk = (chunk * 2).min(self.max_chunk_size.max(chunk));
+            }
+        }
+        Ok(buf.len() - start)
+    }
+}
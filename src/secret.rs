@@ -0,0 +1,85 @@
+use std::sync::atomic::{compiler_fence, Ordering};
+
+pub trait Zeroize {
+    fn zeroize(&mut self);
+}
+
+impl Zeroize for Vec<u8> {
+    // Wipes the whole allocation, including spare capacity left over from
+    // earlier contents, then empties the vector.
+    fn zeroize(&mut self) {
+        let ptr = self.as_mut_ptr();
+        for i in 0..self.capacity() {
+            unsafe { std::ptr::write_volatile(ptr.add(i), 0) };
+        }
+        compiler_fence(Ordering::SeqCst);
+        self.clear();
+    }
+}
+
+impl Zeroize for String {
+    fn zeroize(&mut self) {
+        unsafe { self.as_mut_vec() }.zeroize();
+    }
+}
+
+impl<T: Zeroize> Zeroize for Option<T> {
+    fn zeroize(&mut self) {
+        if let Some(value) = self {
+            value.zeroize();
+        }
+        *self = None;
+    }
+}
+
+// Holds a credential: it is redacted in `Debug` and `Display`, wiped from
+// memory on drop, and only readable through `expose_secret`.
+//
+// Only the buffer the `Secret` owns is wiped. Copies made on the way in or
+// out are not: the `json::JsonValue` the env payload is parsed into, the
+// JSON command documents built for the S3 host calls before they are
+// wrapped, and whatever the caller does with `expose_secret`.
+#[derive(Clone, Default)]
+pub struct Secret<T: Zeroize> {
+    inner: T,
+}
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(inner: T) -> Self {
+        Secret { inner }
+    }
+
+    pub fn expose_secret(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.inner.zeroize();
+    }
+}
+
+impl<T: Zeroize> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+impl<T: Zeroize> std::fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(inner: T) -> Self {
+        Secret::new(inner)
+    }
+}
+
+impl From<&str> for Secret<String> {
+    fn from(inner: &str) -> Self {
+        Secret::new(inner.to_string())
+    }
+}
@@ -8,12 +8,6 @@ pub fn create_tcp_bind_socket(addr: &str) -> Result<u32, SocketErrorKind> {
         if rs == 0 {
             return Ok(fd);
         }
-        Err(match rs {
-            1 => SocketErrorKind::ConnectRefused,
-            2 => SocketErrorKind::ParameterError,
-            3 => SocketErrorKind::ConnectionReset,
-            4 => SocketErrorKind::AddressInUse,
-            _ => unreachable!("unreach."),
-        })
+        Err(SocketErrorKind::from(rs))
     }
 }
@@ -0,0 +1,137 @@
+// Reads the tar archives returned by the IPFS `get` command: ustar and GNU
+// headers, GNU long names and pax `path` records.
+
+use crate::GetEntryKind as EntryKind;
+
+const BLOCK: usize = 512;
+
+pub(crate) struct Entry<'a> {
+    pub(crate) path: String,
+    pub(crate) kind: EntryKind,
+    pub(crate) link_name: Option<String>,
+    pub(crate) data: &'a [u8],
+}
+
+pub(crate) fn entries(mut archive: &[u8]) -> Result<Vec<Entry<'_>>, String> {
+    let mut entries = Vec::new();
+    let mut long_name: Option<String> = None;
+    while archive.len() >= BLOCK {
+        let header = &archive[..BLOCK];
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+        if !checksum_matches(header) {
+            return Err("invalid tar header checksum".to_string());
+        }
+        let size = parse_octal(&header[124..136])?;
+        let data_end = BLOCK.saturating_add(size);
+        if archive.len() < data_end {
+            return Err("truncated tar entry".to_string());
+        }
+        let data = &archive[BLOCK..data_end];
+        let padded = data_end.div_ceil(BLOCK) * BLOCK;
+        archive = &archive[padded.min(archive.len())..];
+
+        let kind = match header[156] {
+            b'0' | 0 | b'7' => EntryKind::File,
+            b'5' => EntryKind::Dir,
+            b'2' => EntryKind::Symlink,
+            b'L' => {
+                long_name = Some(c_string(data));
+                continue;
+            }
+            b'x' => {
+                if let Some(path) = pax_path(data) {
+                    long_name = Some(path);
+                }
+                continue;
+            }
+            // global pax headers and other special entries carry no file
+            _ => continue,
+        };
+        let path = long_name.take().unwrap_or_else(|| header_path(header));
+        let link_name = match kind {
+            EntryKind::Symlink => Some(c_string(&header[157..257])),
+            _ => None,
+        };
+        entries.push(Entry {
+            path: path.trim_end_matches('/').to_string(),
+            kind,
+            link_name,
+            data,
+        });
+    }
+    Ok(entries)
+}
+
+fn header_path(header: &[u8]) -> String {
+    let name = c_string(&header[..100]);
+    let prefix = if &header[257..262] == b"ustar" {
+        c_string(&header[345..500])
+    } else {
+        String::new()
+    };
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+fn checksum_matches(header: &[u8]) -> bool {
+    let Ok(expected) = parse_octal(&header[148..156]) else {
+        return false;
+    };
+    // the checksum field itself counts as spaces
+    let sum: usize = header
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            if (148..156).contains(&i) {
+                32
+            } else {
+                *b as usize
+            }
+        })
+        .sum();
+    sum == expected
+}
+
+fn parse_octal(field: &[u8]) -> Result<usize, String> {
+    let digits = field
+        .iter()
+        .skip_while(|b| **b == b' ')
+        .take_while(|b| (b'0'..=b'7').contains(*b));
+    let mut value = 0usize;
+    for digit in digits {
+        value = value
+            .checked_mul(8)
+            .and_then(|v| v.checked_add((digit - b'0') as usize))
+            .ok_or_else(|| "tar header number overflow".to_string())?;
+    }
+    Ok(value)
+}
+
+fn c_string(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+// Pax records are `"<len> <key>=<value>\n"`.
+fn pax_path(data: &[u8]) -> Option<String> {
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|b| *b == b' ')?;
+        let len: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        if len <= space || len > rest.len() {
+            return None;
+        }
+        let record = &rest[space + 1..len];
+        let record = record.strip_suffix(b"\n").unwrap_or(record);
+        if let Some(path) = record.strip_prefix(b"path=") {
+            return Some(String::from_utf8_lossy(path).into_owned());
+        }
+        rest = &rest[len..];
+    }
+    None
+}
@@ -0,0 +1,439 @@
+// Computes UnixFS CIDs locally, the way `ipfs add --only-hash` does: the
+// content is chunked, every chunk becomes a leaf and the leaves are linked
+// into a balanced dag-pb tree.
+
+use std::{fmt, io};
+
+use crate::{cid::write_varint, Cid, Multihash, Version, CODEC_DAG_PB, CODEC_RAW};
+
+pub const DEFAULT_IMPORT_CHUNK_SIZE: usize = 262144;
+// IPFS nodes refuse blocks bigger than 1MiB.
+pub const MAX_IMPORT_CHUNK_SIZE: usize = 1048576;
+// Links per node of the balanced layout.
+const MAX_LINKS: usize = 174;
+
+// UnixFS data types.
+const DIRECTORY: u64 = 1;
+const FILE: u64 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chunker {
+    Size(usize),
+    Rabin { min: usize, avg: usize, max: usize },
+}
+
+impl Chunker {
+    // Same bounds as the IPFS node uses for `rabin` and `rabin-<avg>`.
+    pub fn rabin(avg: usize) -> Self {
+        Chunker::Rabin {
+            min: avg / 3,
+            avg,
+            max: avg + avg / 2,
+        }
+    }
+
+    // Parses the `--chunker` strings of `ipfs add`: `size-<bytes>`,
+    // `rabin`, `rabin-<avg>` and `rabin-<min>-<avg>-<max>`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split('-');
+        let chunker = match parts.next()? {
+            "default" | "size" => {
+                let size = match parts.next() {
+                    Some(size) => size.parse().ok()?,
+                    None => DEFAULT_IMPORT_CHUNK_SIZE,
+                };
+                Chunker::Size(size)
+            }
+            "rabin" => {
+                let sizes = parts
+                    .by_ref()
+                    .map(|p| p.parse().ok())
+                    .collect::<Option<Vec<usize>>>()?;
+                match sizes[..] {
+                    [] => Chunker::rabin(DEFAULT_IMPORT_CHUNK_SIZE),
+                    [avg] => Chunker::rabin(avg),
+                    [min, avg, max] if min <= avg && avg <= max => Chunker::Rabin { min, avg, max },
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        match chunker {
+            Chunker::Size(size) if size == 0 || size > MAX_IMPORT_CHUNK_SIZE => None,
+            Chunker::Rabin { min, max, .. } if min < 16 || max > MAX_IMPORT_CHUNK_SIZE => None,
+            chunker => Some(chunker),
+        }
+    }
+}
+
+impl Default for Chunker {
+    fn default() -> Self {
+        Chunker::Size(DEFAULT_IMPORT_CHUNK_SIZE)
+    }
+}
+
+impl fmt::Display for Chunker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chunker::Size(size) => write!(f, "size-{}", size),
+            Chunker::Rabin { min, avg, max } => write!(f, "rabin-{}-{}-{}", min, avg, max),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ImportOptions {
+    pub cid_version: Version,
+    // Defaults to raw leaves for CIDv1 and UnixFS leaves for CIDv0, like
+    // the IPFS node.
+    pub raw_leaves: Option<bool>,
+    pub chunker: Chunker,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions {
+            cid_version: Version::V0,
+            raw_leaves: None,
+            chunker: Chunker::default(),
+        }
+    }
+}
+
+impl ImportOptions {
+    fn raw_leaves(&self) -> bool {
+        self.raw_leaves.unwrap_or(self.cid_version == Version::V1)
+    }
+}
+
+// The root of imported content. `size` is the cumulative size of all the
+// blocks, the `Tsize` a parent links it with; `file_size` is the size of
+// the content itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Imported {
+    pub cid: Cid,
+    pub size: u64,
+    pub file_size: u64,
+}
+
+// Chunks and hashes content as it is written, keeping only the leaf links
+// in memory. Also usable as an `io::Write`, e.g. with `io::copy`.
+pub struct UnixfsImporter {
+    opts: ImportOptions,
+    chunker: ChunkerState,
+    pending: Vec<u8>,
+    leaves: Vec<Imported>,
+}
+
+enum ChunkerState {
+    Size(usize),
+    Rabin(Box<Rabin>),
+}
+
+impl UnixfsImporter {
+    pub fn new(opts: ImportOptions) -> Self {
+        let chunker = match opts.chunker {
+            Chunker::Size(size) => ChunkerState::Size(size.max(1)),
+            Chunker::Rabin { min, avg, max } => {
+                ChunkerState::Rabin(Box::new(Rabin::new(min, avg, max)))
+            }
+        };
+        UnixfsImporter {
+            opts,
+            chunker,
+            pending: Vec::new(),
+            leaves: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let cut = match &mut self.chunker {
+                ChunkerState::Size(size) => {
+                    let n = (*size - self.pending.len()).min(data.len());
+                    (self.pending.len() + n == *size).then_some(n)
+                }
+                ChunkerState::Rabin(rabin) => rabin.next_cut(data),
+            };
+            match cut {
+                Some(n) => {
+                    self.pending.extend_from_slice(&data[..n]);
+                    data = &data[n..];
+                    let chunk = std::mem::take(&mut self.pending);
+                    self.add_leaf(&chunk);
+                }
+                None => {
+                    self.pending.extend_from_slice(data);
+                    data = &[];
+                }
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> Imported {
+        if !self.pending.is_empty() || self.leaves.is_empty() {
+            let chunk = std::mem::take(&mut self.pending);
+            self.add_leaf(&chunk);
+        }
+        // a single leaf is the root itself
+        let mut level = self.leaves;
+        while level.len() > 1 {
+            level = level
+                .chunks(MAX_LINKS)
+                .map(|children| file_node(children, self.opts.cid_version))
+                .collect();
+        }
+        level.remove(0)
+    }
+
+    fn add_leaf(&mut self, chunk: &[u8]) {
+        let leaf = if self.opts.raw_leaves() {
+            Imported {
+                cid: Cid::new_v1(CODEC_RAW, Multihash::sha2_256(chunk)),
+                size: chunk.len() as u64,
+                file_size: chunk.len() as u64,
+            }
+        } else {
+            let mut data = Vec::with_capacity(chunk.len() + 16);
+            put_varint(&mut data, 1, FILE);
+            if !chunk.is_empty() {
+                put_bytes(&mut data, 2, chunk);
+            }
+            put_varint(&mut data, 3, chunk.len() as u64);
+            let block = pb_node(&[], &data);
+            Imported {
+                cid: dag_pb_cid(&block, self.opts.cid_version),
+                size: block.len() as u64,
+                file_size: chunk.len() as u64,
+            }
+        };
+        self.leaves.push(leaf);
+    }
+}
+
+impl io::Write for UnixfsImporter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Links sorted by name, as a basic (unsharded) UnixFS directory.
+#[derive(Clone, Debug, Default)]
+pub struct DirectoryBuilder {
+    entries: Vec<(String, Imported)>,
+}
+
+impl DirectoryBuilder {
+    pub fn new() -> Self {
+        DirectoryBuilder::default()
+    }
+
+    // Adds a file or a nested directory, replacing an entry of the same
+    // name.
+    pub fn add(&mut self, name: &str, entry: Imported) {
+        self.entries.retain(|(n, _)| n != name);
+        self.entries.push((name.to_string(), entry));
+    }
+
+    pub fn finish(mut self, cid_version: Version) -> Imported {
+        self.entries
+            .sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+        let mut data = Vec::new();
+        put_varint(&mut data, 1, DIRECTORY);
+        let links = self
+            .entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+            .collect::<Vec<_>>();
+        let block = pb_node(&links, &data);
+        Imported {
+            cid: dag_pb_cid(&block, cid_version),
+            size: block.len() as u64 + links.iter().map(|(_, e)| e.size).sum::<u64>(),
+            file_size: 0,
+        }
+    }
+}
+
+// CID of `data` as a single file, without a host call.
+pub fn compute_cid(data: &[u8], opts: &ImportOptions) -> Cid {
+    let mut importer = UnixfsImporter::new(opts.clone());
+    importer.update(data);
+    importer.finish().cid
+}
+
+// Streams `reader`, e.g. a `std::fs::File`, through the importer.
+pub fn compute_cid_from_reader<R: io::Read>(
+    mut reader: R,
+    opts: &ImportOptions,
+) -> io::Result<Cid> {
+    let mut importer = UnixfsImporter::new(opts.clone());
+    io::copy(&mut reader, &mut importer)?;
+    Ok(importer.finish().cid)
+}
+
+fn file_node(children: &[Imported], cid_version: Version) -> Imported {
+    let file_size = children.iter().map(|c| c.file_size).sum();
+    let mut data = Vec::new();
+    put_varint(&mut data, 1, FILE);
+    put_varint(&mut data, 3, file_size);
+    for child in children {
+        put_varint(&mut data, 4, child.file_size);
+    }
+    let links = children.iter().map(|c| ("", c)).collect::<Vec<_>>();
+    let block = pb_node(&links, &data);
+    Imported {
+        cid: dag_pb_cid(&block, cid_version),
+        size: block.len() as u64 + children.iter().map(|c| c.size).sum::<u64>(),
+        file_size,
+    }
+}
+
+fn dag_pb_cid(block: &[u8], cid_version: Version) -> Cid {
+    let hash = Multihash::sha2_256(block);
+    match cid_version {
+        Version::V0 => Cid::new_v0(hash).expect("sha2-256 is a valid CIDv0 hash"),
+        Version::V1 => Cid::new_v1(CODEC_DAG_PB, hash),
+    }
+}
+
+// dag-pb encodes the links before the data.
+fn pb_node(links: &[(&str, &Imported)], data: &[u8]) -> Vec<u8> {
+    let mut node = Vec::with_capacity(data.len() + links.len() * 48 + 8);
+    for (name, link) in links {
+        let mut pb_link = Vec::with_capacity(48);
+        put_bytes(&mut pb_link, 1, &link.cid.to_bytes());
+        put_bytes(&mut pb_link, 2, name.as_bytes());
+        put_varint(&mut pb_link, 3, link.size);
+        put_bytes(&mut node, 2, &pb_link);
+    }
+    put_bytes(&mut node, 1, data);
+    node
+}
+
+fn put_varint(buf: &mut Vec<u8>, field: u64, value: u64) {
+    write_varint(buf, field << 3);
+    write_varint(buf, value);
+}
+
+fn put_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_varint(buf, (field << 3) | 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+// Content-defined chunking with a rabin fingerprint over a 64-byte window,
+// using the polynomial and cut rules of the IPFS rabin chunker.
+const RABIN_POLYNOMIAL: u64 = 17437180132763653;
+const WINDOW_SIZE: usize = 64;
+
+struct Rabin {
+    out_table: [u64; 256],
+    mod_table: [u64; 256],
+    shift: u32,
+    min: usize,
+    max: usize,
+    mask: u64,
+    window: [u8; WINDOW_SIZE],
+    wpos: usize,
+    digest: u64,
+    // bytes of the current chunk, and bytes still skipped before hashing
+    count: usize,
+    pre: usize,
+}
+
+impl Rabin {
+    fn new(min: usize, avg: usize, max: usize) -> Self {
+        let degree = poly_degree(RABIN_POLYNOMIAL);
+        let mut out_table = [0u64; 256];
+        let mut mod_table = [0u64; 256];
+        for b in 0..256u64 {
+            let mut h = append_byte(0, b as u8);
+            for _ in 0..WINDOW_SIZE - 1 {
+                h = append_byte(h, 0);
+            }
+            out_table[b as usize] = h;
+            mod_table[b as usize] = poly_mod(b << degree) | (b << degree);
+        }
+        // the average size rounds down to a power of two
+        let bits = usize::BITS - 1 - avg.max(1).leading_zeros();
+        let mut rabin = Rabin {
+            out_table,
+            mod_table,
+            shift: degree - 8,
+            min,
+            max,
+            mask: (1u64 << bits) - 1,
+            window: [0; WINDOW_SIZE],
+            wpos: 0,
+            digest: 0,
+            count: 0,
+            pre: 0,
+        };
+        rabin.reset();
+        rabin
+    }
+
+    fn reset(&mut self) {
+        self.window = [0; WINDOW_SIZE];
+        self.wpos = 0;
+        self.digest = 0;
+        self.count = 0;
+        self.slide(1);
+        self.pre = self.min.saturating_sub(WINDOW_SIZE);
+    }
+
+    fn slide(&mut self, b: u8) {
+        let out = self.window[self.wpos];
+        self.window[self.wpos] = b;
+        self.digest ^= self.out_table[out as usize];
+        self.wpos = (self.wpos + 1) % WINDOW_SIZE;
+        let index = self.digest >> self.shift;
+        self.digest = (self.digest << 8) | b as u64;
+        self.digest ^= self.mod_table[index as usize];
+    }
+
+    // Returns how many bytes of `data` end the current chunk, if it ends
+    // within `data`.
+    fn next_cut(&mut self, data: &[u8]) -> Option<usize> {
+        let skip = self.pre.min(data.len());
+        self.pre -= skip;
+        self.count += skip;
+        for (i, b) in data.iter().enumerate().skip(skip) {
+            self.slide(*b);
+            self.count += 1;
+            if self.count < self.min {
+                continue;
+            }
+            if self.digest & self.mask == 0 || self.count >= self.max {
+                self.reset();
+                return Some(i + 1);
+            }
+        }
+        None
+    }
+}
+
+fn poly_degree(p: u64) -> u32 {
+    63 - p.leading_zeros()
+}
+
+// Remainder of the division by the polynomial over GF(2).
+fn poly_mod(mut x: u64) -> u64 {
+    let degree = poly_degree(RABIN_POLYNOMIAL);
+    while x != 0 && poly_degree(x) >= degree {
+        x ^= RABIN_POLYNOMIAL << (poly_degree(x) - degree);
+    }
+    x
+}
+
+fn append_byte(h: u64, b: u8) -> u64 {
+    poly_mod((h << 8) | b as u64)
+}
@@ -0,0 +1,38 @@
+// Minimal URL splitting used where the SDK has to reason about request
+// destinations (cookie scoping). Not a general purpose parser.
+pub(crate) struct Url<'a> {
+    pub scheme: String,
+    pub host: String,
+    pub path: &'a str,
+}
+
+impl<'a> Url<'a> {
+    pub(crate) fn parse(url: &'a str) -> Option<Self> {
+        let (scheme, rest) = url.split_once("://")?;
+        let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let authority = &rest[..authority_end];
+        let authority = authority
+            .rsplit_once('@')
+            .map(|(_, host)| host)
+            .unwrap_or(authority);
+        let host = if authority.starts_with('[') {
+            &authority[..authority.find(']')? + 1]
+        } else {
+            authority.split(':').next()?
+        };
+        if host.is_empty() {
+            return None;
+        }
+        let path = &rest[authority_end..];
+        let path = &path[..path.find(['?', '#']).unwrap_or(path.len())];
+        Some(Url {
+            scheme: scheme.to_ascii_lowercase(),
+            host: host.to_ascii_lowercase(),
+            path: if path.is_empty() { "/" } else { path },
+        })
+    }
+
+    pub(crate) fn is_secure(&self) -> bool {
+        self.scheme == "https" || self.scheme == "wss"
+    }
+}
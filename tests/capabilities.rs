@@ -0,0 +1,79 @@
+use blockless_sdk::capabilities::*;
+
+#[test]
+fn parses_reports() {
+    let report = r#"{
+        "modules": {"blockless_http": 1, "blockless_ipfs": 2, "blockless_s3": "v1"},
+        "ipfs": ["files_ls", "files_read", 3],
+        "s3": ["get"]
+    }"#;
+    let caps = Capabilities::parse(report).unwrap();
+    assert!(caps.is_reported());
+    assert!(caps.has_module(HTTP_MODULE));
+    assert_eq!(caps.abi_version(HTTP_MODULE), Some(1));
+    assert_eq!(caps.abi_version(IPFS_MODULE), Some(2));
+    // present, but with a version that isn't a number
+    assert!(caps.has_module(S3_MODULE));
+    assert_eq!(caps.abi_version(S3_MODULE), None);
+    assert!(!caps.has_module(CGI_MODULE));
+    assert_eq!(caps.modules().count(), 3);
+
+    assert_eq!(
+        caps.ipfs_commands().unwrap(),
+        ["files_ls".to_string(), "files_read".to_string()]
+    );
+    assert_eq!(caps.supports_ipfs_command("files_read"), Some(true));
+    assert_eq!(caps.supports_ipfs_command("files_write"), Some(false));
+    assert_eq!(caps.supports_s3_command("get"), Some(true));
+    assert_eq!(caps.supports_s3_command("put"), Some(false));
+}
+
+#[test]
+fn rejects_reports_that_are_not_objects() {
+    for report in ["", "not json", "[]", "42", r#""modules""#, "null"] {
+        assert!(Capabilities::parse(report).is_none(), "{}", report);
+    }
+}
+
+#[test]
+fn missing_parts_are_unknown() {
+    let caps = Capabilities::parse(r#"{"ipfs": "files_ls"}"#).unwrap();
+    assert!(caps.is_reported());
+    assert_eq!(caps.modules().count(), 0);
+    assert!(!caps.has_module(MEMORY_MODULE));
+    // a command list that isn't an array says nothing about the commands
+    assert_eq!(caps.ipfs_commands(), None);
+    assert_eq!(caps.supports_ipfs_command("files_ls"), None);
+    assert_eq!(caps.supports_s3_command("get"), None);
+
+    let caps = Capabilities::parse(r#"{"modules": {}, "s3": []}"#).unwrap();
+    assert_eq!(caps.supports_s3_command("get"), Some(false));
+}
+
+#[test]
+fn compiled_modules_follow_features() {
+    let expected = [
+        (MEMORY_MODULE, true),
+        (HTTP_MODULE, cfg!(feature = "http")),
+        (IPFS_MODULE, cfg!(feature = "ipfs")),
+        (S3_MODULE, cfg!(feature = "s3")),
+        (CGI_MODULE, cfg!(feature = "cgi")),
+        (SOCKET_MODULE, cfg!(feature = "socket")),
+    ];
+    for (module, enabled) in expected {
+        assert_eq!(COMPILED_MODULES.contains(&module), enabled, "{}", module);
+    }
+    assert_eq!(
+        COMPILED_MODULES.len(),
+        expected.iter().filter(|(_, enabled)| *enabled).count()
+    );
+
+    let caps = detect();
+    assert!(!caps.is_reported());
+    for (module, enabled) in expected {
+        assert_eq!(caps.has_module(module), enabled, "{}", module);
+        assert_eq!(caps.abi_version(module), None);
+    }
+    assert_eq!(caps.ipfs_commands(), None);
+    assert_eq!(caps.s3_commands(), None);
+}
@@ -0,0 +1,71 @@
+use blockless_sdk::*;
+
+const EMPTY_DIR_V0: &str = "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn";
+const EMPTY_DIR_V1: &str = "bafybeiczsscdsbs7ffqz55asqdf3smv6klcw3gofszvwlyarci47bgf354";
+
+#[test]
+fn converts_between_versions() {
+    let v0: Cid = EMPTY_DIR_V0.parse().unwrap();
+    assert_eq!(v0.version(), Version::V0);
+    assert_eq!(v0.codec(), CODEC_DAG_PB);
+    assert_eq!(v0.hash().code(), SHA2_256);
+    assert_eq!(v0.to_string(), EMPTY_DIR_V0);
+
+    let v1 = v0.clone().into_v1();
+    assert_eq!(v1.to_string(), EMPTY_DIR_V1);
+    assert_eq!(v1, EMPTY_DIR_V1.parse().unwrap());
+    assert_ne!(v0, v1);
+    assert_eq!(v1.into_v0().unwrap(), v0);
+}
+
+#[test]
+fn round_trips_multibases() {
+    let cid: Cid = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"
+        .parse()
+        .unwrap();
+    assert_eq!(cid.codec(), CODEC_RAW);
+    let base58 = cid.to_string_of_base(Base::Base58Btc);
+    assert!(base58.starts_with('z'));
+    assert_eq!(base58.parse::<Cid>().unwrap(), cid);
+    let upper = cid.to_string().to_uppercase();
+    assert_eq!(upper.parse::<Cid>().unwrap(), cid);
+    assert_eq!(Cid::from_bytes(&cid.to_bytes()).unwrap(), cid);
+}
+
+#[test]
+fn rejects_invalid_cids() {
+    assert!(matches!(
+        "".parse::<Cid>(),
+        Err(CidErrorKind::InvalidLength)
+    ));
+    assert!(matches!(
+        "mAXASIA".parse::<Cid>(),
+        Err(CidErrorKind::UnsupportedMultibase('m'))
+    ));
+    assert!(matches!(
+        "b0000".parse::<Cid>(),
+        Err(CidErrorKind::InvalidMultibase)
+    ));
+    // base32 that no encoder produces
+    for cid in [
+        // nonzero bits after the last byte
+        "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5f",
+        // lengths of 1 and 3 modulo 8
+        "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5",
+        "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5ea",
+        // mixed case
+        "Bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e",
+        "bAFKREIFZJUT3TE2NHYEKKLSS27NH3K72YSCO7Y32KOAO5EEI66WOF36N5E",
+    ] {
+        assert!(
+            matches!(cid.parse::<Cid>(), Err(CidErrorKind::InvalidMultibase)),
+            "{}",
+            cid
+        );
+    }
+    // raw content can't be a CIDv0
+    let raw: Cid = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"
+        .parse()
+        .unwrap();
+    assert!(matches!(raw.into_v0(), Err(CidErrorKind::InvalidV0)));
+}
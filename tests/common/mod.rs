@@ -0,0 +1,62 @@
+// The IPFS transport shared by the test binaries.
+#![allow(dead_code)]
+
+use std::cell::{Cell, RefCell};
+
+use blockless_sdk::{IPFSErrorKind, IpfsRequest, IpfsResponse, IpfsTransport};
+
+pub struct Recorded {
+    pub api: String,
+    pub args: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+type Respond = Box<dyn Fn(&IpfsRequest) -> (u32, Vec<u8>)>;
+
+// Answers commands with the status and body from `respond` and records the
+// requests. The next `failures` calls fail with `RequestError` instead.
+pub struct MockTransport {
+    respond: Respond,
+    pub requests: RefCell<Vec<Recorded>>,
+    pub failures: Cell<usize>,
+}
+
+impl MockTransport {
+    // Answers every command with the same response.
+    pub fn new(status: u32, body: impl Into<Vec<u8>>) -> Self {
+        let body = body.into();
+        MockTransport::with_responder(move |_| (status, body.clone()))
+    }
+
+    pub fn with_responder(respond: impl Fn(&IpfsRequest) -> (u32, Vec<u8>) + 'static) -> Self {
+        MockTransport {
+            respond: Box::new(respond),
+            requests: RefCell::new(Vec::new()),
+            failures: Cell::new(0),
+        }
+    }
+}
+
+impl IpfsTransport for MockTransport {
+    fn call(
+        &self,
+        request: &IpfsRequest,
+        body: Option<&[u8]>,
+    ) -> Result<IpfsResponse, IPFSErrorKind> {
+        let args = request
+            .args()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        self.requests.borrow_mut().push(Recorded {
+            api: request.api().to_string(),
+            args,
+            body: body.map(|body| body.to_vec()),
+        });
+        if self.failures.get() > 0 {
+            self.failures.set(self.failures.get() - 1);
+            return Err(IPFSErrorKind::RequestError);
+        }
+        let (status, body) = (self.respond)(request);
+        Ok(IpfsResponse { status, body })
+    }
+}
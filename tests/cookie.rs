@@ -0,0 +1,108 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use blockless_sdk::cookie::*;
+
+const URL: &str = "https://example.com/app/index.html";
+
+fn parse(set_cookie: &str) -> Cookie {
+    Cookie::parse(set_cookie, URL).unwrap()
+}
+
+#[test]
+fn non_positive_max_age_expires_now() {
+    for set_cookie in ["a=1; Max-Age=0", "a=1; Max-Age=-5"] {
+        assert_eq!(parse(set_cookie).expires, Some(UNIX_EPOCH));
+    }
+    let mut jar = CookieJar::new();
+    jar.store("a=1", URL);
+    jar.store("a=2; Max-Age=0", URL);
+    assert_eq!(jar.cookie_header(URL), None);
+}
+
+#[test]
+fn huge_max_age_is_capped() {
+    let cookie = parse("a=1; Max-Age=9223372036854775807");
+    let expires = cookie.expires.unwrap();
+    assert!(expires <= SystemTime::now() + MAX_COOKIE_AGE);
+    assert!(expires > SystemTime::now() + MAX_COOKIE_AGE - Duration::from_secs(60));
+}
+
+#[test]
+fn malformed_expires_is_ignored() {
+    for expires in [
+        "soon",
+        "Wed, 32 Oct 2015 07:28:00 GMT",
+        "Wed, 21 Foo 2015 07:28:00 GMT",
+        "Wed, 21 Oct 2015 25:28:00 GMT",
+        "Wed, 21 Oct 99999999999999 07:28:00 GMT",
+        "Wed, 21 Oct 9223372036854775807 07:28:00 GMT",
+    ] {
+        let cookie = parse(&format!("a=1; Expires={}", expires));
+        assert_eq!(cookie.expires, None, "{}", expires);
+    }
+}
+
+#[test]
+fn parses_expires_dates() {
+    let at = |secs| Some(UNIX_EPOCH + Duration::from_secs(secs));
+    assert_eq!(
+        parse("a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT").expires,
+        at(1445412480)
+    );
+    // two-digit years below 70 are 20xx, the others 19xx
+    assert_eq!(
+        parse("a=1; Expires=Wednesday, 21-Oct-15 07:28:00 GMT").expires,
+        at(1445412480)
+    );
+    assert_eq!(
+        parse("a=1; Expires=Wed, 01-Jan-69 00:00:00 GMT").expires,
+        at(3124224000)
+    );
+    assert_eq!(
+        parse("a=1; Expires=Thu, 01-Jan-70 00:00:00 GMT").expires,
+        at(0)
+    );
+}
+
+#[test]
+fn max_age_wins_over_expires() {
+    let cookie = parse("a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=60");
+    assert!(!cookie.is_expired(SystemTime::now()));
+}
+
+#[test]
+fn empty_domains_make_host_only_cookies() {
+    for set_cookie in ["a=1; Domain=", "a=1; Domain=.", "a=1; Domain= . "] {
+        let cookie = parse(set_cookie);
+        assert_eq!(cookie.domain, "example.com", "{}", set_cookie);
+        assert!(cookie.host_only, "{}", set_cookie);
+    }
+    let mut jar = CookieJar::new();
+    jar.store("a=1; Domain=.; Path=/", URL);
+    assert_eq!(jar.cookie_header("https://example.com/").unwrap(), "a=1");
+    assert_eq!(jar.cookie_header("https://sub.example.com/"), None);
+    assert_eq!(jar.cookie_header("https://other.com/"), None);
+
+    // only one leading dot is dropped
+    let cookie = parse("a=1; Domain=.Example.com");
+    assert_eq!(cookie.domain, "example.com");
+    assert!(!cookie.host_only);
+    assert!(Cookie::parse("a=1; Domain=..example.com", URL).is_none());
+}
+
+#[test]
+fn secure_cookies_need_a_secure_origin() {
+    assert!(parse("a=1; Secure").secure);
+    assert!(Cookie::parse("a=1; Secure", "http://example.com/").is_none());
+    assert!(Cookie::parse("a=1", "http://example.com/").is_some());
+
+    let mut jar = CookieJar::new();
+    jar.store("a=1; Secure, b=2", "http://example.com/");
+    jar.store("c=3; Secure", "https://example.com/");
+    assert_eq!(jar.iter().count(), 2);
+    assert_eq!(jar.cookie_header("http://example.com/").unwrap(), "b=2");
+    assert_eq!(
+        jar.cookie_header("https://example.com/").unwrap(),
+        "b=2; c=3"
+    );
+}
@@ -0,0 +1,85 @@
+use blockless_sdk::{env::*, EnvErrorKind};
+
+#[test]
+fn parses_json_payloads() {
+    let env = Env::parse(br#"{"RPC_URL": "https://rpc", "RETRIES": 3, "DEBUG": true}"#).unwrap();
+    assert_eq!(env.len(), 3);
+    assert_eq!(env.get("RPC_URL"), Some("https://rpc"));
+    // values that aren't strings keep their JSON form
+    assert_eq!(env.get("RETRIES"), Some("3"));
+    assert_eq!(env.get("DEBUG"), Some("true"));
+    assert_eq!(env.get("MISSING"), None);
+}
+
+#[test]
+fn ignores_nul_padding() {
+    let mut payload = br#"{"A": "1"}"#.to_vec();
+    payload.resize(64, 0);
+    let env = Env::parse(&payload).unwrap();
+    assert_eq!(env.iter().collect::<Vec<_>>(), [("A", "1")]);
+
+    let env = Env::parse(b"\0\0A=1\0B=2\nC=3\0\0").unwrap();
+    assert_eq!(
+        env.iter().collect::<Vec<_>>(),
+        [("A", "1"), ("B", "2"), ("C", "3")]
+    );
+    assert!(Env::parse(&[0; 16]).unwrap().is_empty());
+}
+
+#[test]
+fn trims_names_only() {
+    let env = Env::parse(b" GREETING = hello world \n\tPATH=/bin:/usr/bin\n").unwrap();
+    assert_eq!(env.get("GREETING"), Some(" hello world "));
+    assert_eq!(env.get("PATH"), Some("/bin:/usr/bin"));
+    assert_eq!(env.get("SEP"), None);
+    assert_eq!(Env::parse(b"SEP=a=b").unwrap().get("SEP"), Some("a=b"));
+}
+
+#[test]
+fn rejects_invalid_payloads() {
+    for payload in [&b"NO_EQUALS_SIGN"[..], b"{\"A\": ", b"\xff=1"] {
+        assert!(matches!(
+            Env::parse(payload),
+            Err(EnvErrorKind::InvalidPayload)
+        ));
+    }
+}
+
+#[test]
+fn fails_on_payloads_that_are_too_large() {
+    let mut reads = 0;
+    let env = Env::load_from(|buf| {
+        reads += 1;
+        buf.fill(b'A');
+        Ok(buf.len() as u32)
+    });
+    assert!(matches!(env, Err(EnvErrorKind::TooLarge)));
+    assert!(reads > 1);
+
+    // a payload just below the limit, NUL padded, still fits
+    let env = Env::load_from(|buf| {
+        buf[..3].copy_from_slice(b"A=1");
+        Ok(MAX_PAYLOAD_SIZE as u32 - 1)
+    })
+    .unwrap();
+    assert_eq!(env.get("A"), Some("1"));
+}
+
+#[test]
+fn reports_malformed_and_missing_values() {
+    let env = Env::parse(b"PORT=80a0\nRETRIES= 3 ").unwrap();
+    match env.get_parse::<u16>("PORT") {
+        Err(EnvErrorKind::Malformed { name, reason }) => {
+            assert_eq!(name, "PORT");
+            assert_eq!(reason, "invalid digit found in string");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(env.get_parse::<u16>("RETRIES").unwrap(), Some(3));
+    assert_eq!(env.get_parse::<u16>("TIMEOUT").unwrap(), None);
+    assert_eq!(env.get_parse_or("TIMEOUT", 30u16).unwrap(), 30);
+    assert!(matches!(
+        env.require("TIMEOUT"),
+        Err(EnvErrorKind::Missing(name)) if name == "TIMEOUT"
+    ));
+}
@@ -0,0 +1,201 @@
+use blockless_sdk::{
+    AWSS3ErrorKind, BlocklessError, CGIErrorKind, CidErrorKind, HttpErrorKind, IPFSErrorKind,
+    OAuth2ErrorKind, SocketErrorKind,
+};
+
+type AbiTable = &'static [(u32, &'static str)];
+
+// The host ABIs, written out by hand so a change to the tables in the SDK
+// shows up here.
+const HTTP_ABI: AbiTable = &[
+    (1, "InvalidHandle"),
+    (2, "MemoryAccessError"),
+    (3, "BufferTooSmall"),
+    (4, "HeaderNotFound"),
+    (5, "Utf8Error"),
+    (6, "DestinationNotAllowed"),
+    (7, "InvalidMethod"),
+    (8, "InvalidEncoding"),
+    (9, "InvalidUrl"),
+    (10, "RequestError"),
+    (11, "RuntimeError"),
+    (12, "TooManySessions"),
+    (13, "PermissionDeny"),
+];
+
+const SOCKET_ABI: AbiTable = &[
+    (1, "ConnectRefused"),
+    (2, "ParameterError"),
+    (3, "ConnectionReset"),
+    (4, "AddressInUse"),
+];
+
+const IPFS_ABI: AbiTable = &[
+    (0, "Success"),
+    (1, "InvalidHandle"),
+    (2, "Utf8Error"),
+    (3, "InvalidMethod"),
+    (4, "InvalidParameter"),
+    (5, "InvalidEncoding"),
+    (6, "RequestError"),
+    (7, "RuntimeError"),
+    (8, "TooManySessions"),
+    (9, "InvalidDriver"),
+    (10, "PermissionDeny"),
+];
+
+const S3_ABI: AbiTable = &[
+    (0, "Success"),
+    (1, "InvalidHandle"),
+    (2, "Utf8Error"),
+    (3, "InvalidMethod"),
+    (4, "InvalidParameter"),
+    (5, "InvalidEncoding"),
+    (6, "CredentialsError"),
+    (7, "RegionError"),
+    (8, "RequestError"),
+    (9, "RuntimeError"),
+    (10, "TooManySessions"),
+    (11, "InvalidDriver"),
+    (12, "PermissionDeny"),
+];
+
+// Checks a kind against its ABI table: every host code maps to the variant
+// of the same name and back, and codes outside the table are kept as
+// `Unknown(code)`.
+fn check_abi<K>(table: &[(u32, &str)], host_codes: &[(u32, &str)])
+where
+    K: From<u32> + Into<u32> + std::fmt::Debug,
+{
+    assert_eq!(host_codes, table);
+    for &(code, name) in table {
+        let kind = K::from(code);
+        assert_eq!(format!("{:?}", kind), name, "code {}", code);
+        assert_eq!(kind.into(), code, "{}", name);
+    }
+    let max = table.iter().map(|&(code, _)| code).max().unwrap_or(0);
+    for code in [max + 1, max + 100, 999, u32::MAX] {
+        let kind = K::from(code);
+        assert_eq!(format!("{:?}", kind), format!("Unknown({})", code));
+        assert_eq!(kind.into(), code);
+    }
+}
+
+#[test]
+fn http_codes_round_trip() {
+    check_abi::<HttpErrorKind>(HTTP_ABI, HttpErrorKind::HOST_CODES);
+}
+
+#[test]
+fn socket_codes_round_trip() {
+    check_abi::<SocketErrorKind>(SOCKET_ABI, SocketErrorKind::HOST_CODES);
+}
+
+#[test]
+fn ipfs_codes_round_trip() {
+    check_abi::<IPFSErrorKind>(IPFS_ABI, IPFSErrorKind::HOST_CODES);
+}
+
+#[test]
+fn s3_codes_round_trip() {
+    check_abi::<AWSS3ErrorKind>(S3_ABI, AWSS3ErrorKind::HOST_CODES);
+}
+
+#[test]
+fn cgi_codes_are_kept() {
+    check_abi::<CGIErrorKind>(&[], CGIErrorKind::HOST_CODES);
+    assert!(matches!(CGIErrorKind::from(3), CGIErrorKind::Unknown(3)));
+    assert_eq!(CGIErrorKind::Unknown(3).host_code(), Some(3));
+    assert_eq!(CGIErrorKind::NoCommandError.host_code(), None);
+    for kind in [
+        CGIErrorKind::ListError(7),
+        CGIErrorKind::ExecError(7),
+        CGIErrorKind::ReadError(7),
+    ] {
+        assert_eq!(kind.host_code(), Some(7));
+        assert_eq!(u32::from(&kind), 7);
+    }
+}
+
+#[test]
+fn blockless_errors_keep_host_codes() {
+    let host = [
+        (BlocklessError::from(HttpErrorKind::from(3)), 3),
+        (BlocklessError::from(HttpErrorKind::Unknown(77)), 77),
+        (BlocklessError::from(SocketErrorKind::from(3)), 3),
+        (BlocklessError::from(SocketErrorKind::Unknown(77)), 77),
+        (BlocklessError::from(IPFSErrorKind::from(3)), 3),
+        (BlocklessError::from(IPFSErrorKind::Unknown(77)), 77),
+        (BlocklessError::from(AWSS3ErrorKind::from(3)), 3),
+        (BlocklessError::from(AWSS3ErrorKind::Unknown(77)), 77),
+        (BlocklessError::from(CGIErrorKind::ExecError(3)), 3),
+        (BlocklessError::from(CGIErrorKind::Unknown(77)), 77),
+        (
+            BlocklessError::from(OAuth2ErrorKind::HttpError(HttpErrorKind::from(3))),
+            3,
+        ),
+    ];
+    for (error, code) in host {
+        assert_eq!(error.host_code(), Some(code), "{:?}", error);
+        let error = error.context("outer");
+        assert_eq!(error.host_code(), Some(code), "{:?}", error);
+    }
+
+    let sdk = [
+        BlocklessError::from(HttpErrorKind::BodyTooLarge),
+        BlocklessError::from(CGIErrorKind::NoCommandError),
+        BlocklessError::from(OAuth2ErrorKind::MissingRefreshToken),
+        BlocklessError::from(CidErrorKind::InvalidMultibase),
+    ];
+    for error in sdk {
+        assert_eq!(error.host_code(), None, "{:?}", error);
+    }
+}
+
+#[test]
+fn abi_tables_are_contiguous() {
+    let tables: [(&str, AbiTable, u32); 4] = [
+        ("http", HttpErrorKind::HOST_CODES, 1),
+        ("socket", SocketErrorKind::HOST_CODES, 1),
+        ("ipfs", IPFSErrorKind::HOST_CODES, 0),
+        ("s3", AWSS3ErrorKind::HOST_CODES, 0),
+    ];
+    for (module, table, first) in tables {
+        for (i, &(code, name)) in table.iter().enumerate() {
+            assert_eq!(code, first + i as u32, "{} {}", module, name);
+        }
+    }
+}
+
+#[test]
+fn s3_runtime_error_is_code_9() {
+    assert!(matches!(
+        AWSS3ErrorKind::from(9),
+        AWSS3ErrorKind::RuntimeError
+    ));
+    assert!(matches!(
+        AWSS3ErrorKind::from(10),
+        AWSS3ErrorKind::TooManySessions
+    ));
+    assert!(matches!(
+        AWSS3ErrorKind::from(12),
+        AWSS3ErrorKind::PermissionDeny
+    ));
+}
+
+#[test]
+fn sdk_errors_use_reserved_codes() {
+    for kind in [
+        HttpErrorKind::InvalidDriver,
+        HttpErrorKind::BodyTooLarge,
+        HttpErrorKind::ReadTimeout,
+    ] {
+        assert_eq!(kind.host_code(), None);
+        let code = u32::from(&kind);
+        assert!(code >= 1000);
+        assert_eq!(
+            format!("{:?}", HttpErrorKind::from(code)),
+            format!("{:?}", kind)
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use blockless_sdk::{input::*, InputErrorKind};
+
+fn auto<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, InputErrorKind> {
+    decode(bytes, Format::Auto)
+}
+
+#[test]
+fn decodes_json() {
+    let map: BTreeMap<String, u32> = auto(b" \n{\"a\": 1}").unwrap();
+    assert_eq!(map["a"], 1);
+    assert_eq!(auto::<i32>(b"-12").unwrap(), -12);
+    assert!(!auto::<bool>(b"\xef\xbb\xbffalse").unwrap());
+}
+
+#[test]
+fn blank_input_is_empty() {
+    for bytes in [&b""[..], b" \n"] {
+        assert!(matches!(
+            auto::<BTreeMap<String, u32>>(bytes),
+            Err(InputErrorKind::Empty)
+        ));
+    }
+}
+
+#[test]
+fn reports_the_json_error_for_bad_json() {
+    match auto::<BTreeMap<String, u32>>(b"{\"a\": }") {
+        Err(InputErrorKind::Decode { format, .. }) => assert_eq!(format, "json"),
+        other => panic!("unexpected {:?}", other.map(|_| ())),
+    }
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn decodes_cbor_scalars_that_look_like_json() {
+    // a short text string, 0x66 is `f`
+    assert_eq!(auto::<String>(b"\x66foobar").unwrap(), "foobar");
+    // a negative integer, 0x2d is `-`
+    assert_eq!(auto::<i64>(&[0x2d]).unwrap(), -14);
+    // 0x20 is both a space and CBOR -1
+    assert_eq!(auto::<i64>(&[0x20]).unwrap(), -1);
+    // the self-describe tag
+    assert_eq!(auto::<u64>(&[0xd9, 0xd9, 0xf7, 0x18, 0x64]).unwrap(), 100);
+    // a trailing byte is not CBOR
+    assert!(decode::<u64>(&[0x01, 0x02], Format::Cbor).is_err());
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn decodes_msgpack_scalars_that_look_like_json() {
+    // positive fixints in the JSON range: `-`, `t`, `{`
+    assert_eq!(auto::<u32>(&[0x2d]).unwrap(), 45);
+    assert_eq!(auto::<u8>(b"t").unwrap(), b't');
+    assert_eq!(auto::<u8>(b"{").unwrap(), b'{');
+    // a fixmap {"a": 1}
+    let map: BTreeMap<String, u32> = auto(&[0x81, 0xa1, b'a', 0x01]).unwrap();
+    assert_eq!(map["a"], 1);
+}
+
+#[cfg(all(feature = "cbor", feature = "msgpack"))]
+#[test]
+fn falls_back_across_binary_formats() {
+    // CBOR reads 0x2d as -14, which a u32 rejects; MessagePack reads 45
+    assert_eq!(auto::<i64>(&[0x2d]).unwrap(), -14);
+    assert_eq!(auto::<u32>(&[0x2d]).unwrap(), 45);
+    // MessagePack str8 of 3 bytes, which CBOR can't decode
+    assert_eq!(
+        auto::<String>(&[0xd9, 0x03, b'a', b'b', b'c']).unwrap(),
+        "abc"
+    );
+}
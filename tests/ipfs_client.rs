@@ -0,0 +1,614 @@
+use std::cell::RefCell;
+
+use blockless_sdk::*;
+
+mod common;
+
+use common::MockTransport;
+
+#[test]
+fn builds_typed_requests() {
+    let client = IpfsClient::with_transport(MockTransport::new(200, ""));
+    client
+        .remove(
+            "/foo",
+            &FileRemoveOptions {
+                recursive: true,
+                force: false,
+            },
+        )
+        .unwrap();
+    let mut opts = FileWriteOptions::new("/foo/bar.txt");
+    opts.truncate = true;
+    client.write(&opts, b"foo bar").unwrap();
+
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests[0].api, "files/rm");
+    assert_eq!(
+        requests[0].args,
+        [
+            ("arg".to_string(), "/foo".to_string()),
+            ("recursive".to_string(), "true".to_string()),
+            ("force".to_string(), "false".to_string()),
+        ]
+    );
+    assert_eq!(requests[1].api, "files/write");
+    assert_eq!(requests[1].body.as_deref(), Some(&b"foo bar"[..]));
+}
+
+#[test]
+fn lists_entries() {
+    let body = r#"{"Entries":[{"Name":"bar.txt","Type":0,"Size":7,"Hash":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"}]}"#;
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let files = client
+        .list(Some("/foo"), &FileListOptions::default())
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(
+        files[0].to_string(),
+        "name:bar.txt, size:7, type:0, hash:QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"
+    );
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests[0].args, [("arg".to_string(), "/foo".to_string())]);
+}
+
+#[test]
+fn decodes_error_body() {
+    let body = r#"{"Message":"file does not exist","Code":0,"Type":"error"}"#;
+    let client = IpfsClient::with_transport(MockTransport::new(500, body));
+    match client.stat("/missing", &FileStatOptions::default()) {
+        Err(IPFSErrorKind::ApiError {
+            status,
+            code,
+            message,
+        }) => {
+            assert_eq!(status, 500);
+            assert_eq!(code, Some(0));
+            assert_eq!(message, "file does not exist");
+        }
+        _ => panic!("expected an api error"),
+    }
+
+    let client = IpfsClient::with_transport(MockTransport::new(404, "404 page not found\n"));
+    match client.create_dir("/foo", &CreateDirOptions::default()) {
+        Err(IPFSErrorKind::ApiError { code, message, .. }) => {
+            assert_eq!(code, None);
+            assert_eq!(message, "404 page not found");
+        }
+        _ => panic!("expected an api error"),
+    }
+}
+
+#[test]
+fn adds_and_returns_root() {
+    let body = concat!(
+        r#"{"Name":"","Bytes":262144}"#,
+        "\n",
+        r#"{"Name":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o","Hash":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o","Size":"20"}"#,
+        "\n",
+    );
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let opts = AddOptions {
+        cid_version: Some(1),
+        only_hash: true,
+        ..Default::default()
+    };
+    let added = client.add(b"hello world\n", &opts).unwrap();
+    assert_eq!(
+        added.hash.to_string(),
+        "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"
+    );
+    assert_eq!(added.size, 20);
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests[0].api, "add");
+    assert_eq!(
+        requests[0].args,
+        [
+            ("cid-version".to_string(), "1".to_string()),
+            ("only-hash".to_string(), "true".to_string()),
+        ]
+    );
+}
+
+fn tar_header(name: &str, typeflag: u8, size: usize) -> Vec<u8> {
+    let mut header = vec![0u8; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    header[156] = typeflag;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[148..156].copy_from_slice(b"        ");
+    let sum: u32 = header.iter().map(|b| *b as u32).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+    header
+}
+
+#[test]
+fn gets_directory_tree() {
+    let mut archive = tar_header("QmDir", b'5', 0);
+    archive.extend(tar_header("QmDir/hello.txt", b'0', 12));
+    archive.extend(b"hello world\n");
+    archive.resize(archive.len() + 500, 0);
+    archive.extend(vec![0u8; 1024]);
+
+    let client = IpfsClient::with_transport(MockTransport::new(200, archive));
+    let entries = client.get("QmDir").unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].path, "QmDir");
+    assert_eq!(entries[0].kind, GetEntryKind::Dir);
+    assert_eq!(entries[1].path, "QmDir/hello.txt");
+    assert_eq!(entries[1].kind, GetEntryKind::File);
+    assert_eq!(entries[1].data, b"hello world\n");
+}
+
+#[test]
+fn lists_pins_by_type() {
+    let body = r#"{"Keys":{"QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn":{"Type":"recursive"},"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o":{"Type":"direct"}}}"#;
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let opts = PinLsOptions {
+        pin_type: Some(PinType::Recursive),
+        ..Default::default()
+    };
+    let pins = client.pin_ls(&opts).unwrap();
+    assert_eq!(pins.len(), 2);
+    assert_eq!(
+        pins[0].cid.to_string(),
+        "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+    );
+    assert_eq!(pins[0].pin_type, PinType::Recursive);
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests[0].api, "pin/ls");
+    assert_eq!(
+        requests[0].args,
+        [("type".to_string(), "recursive".to_string())]
+    );
+}
+
+#[test]
+fn lists_remote_pins() {
+    let body = concat!(
+        r#"{"Status":"pinned","Cid":"QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn","Name":"output"}"#,
+        "\n",
+        r#"{"Status":"queued","Cid":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o","Name":""}"#,
+        "\n",
+    );
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let mut query = RemotePinQuery::new("pinata");
+    query.status = vec![RemotePinStatus::Pinned, RemotePinStatus::Queued];
+    let pins = client.pin_remote_ls(&query).unwrap();
+    assert_eq!(pins.len(), 2);
+    assert_eq!(pins[0].name, "output");
+    assert_eq!(pins[1].status, RemotePinStatus::Queued);
+    let requests = client.transport().requests.borrow();
+    assert_eq!(
+        requests[0].args,
+        [
+            ("service".to_string(), "pinata".to_string()),
+            ("status".to_string(), "pinned,queued".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn writes_files_in_chunks() {
+    use std::io::Write;
+
+    let client = IpfsClient::with_transport(MockTransport::new(200, ""));
+    let mut opts = FileWriteOptions::new("/foo/big.bin");
+    opts.truncate = true;
+    let progress = RefCell::new(Vec::new());
+    let mut writer = client.file_writer(opts);
+    writer.set_chunk_size(4);
+    writer.set_progress(|written| progress.borrow_mut().push(written));
+    writer.write_all(b"0123456789").unwrap();
+    assert_eq!(writer.finish().unwrap(), 10);
+
+    let requests = client.transport().requests.borrow();
+    let calls = requests
+        .iter()
+        .map(|r| {
+            let arg = |name: &str| {
+                r.args
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| v.clone())
+                    .unwrap()
+            };
+            (
+                arg("offset"),
+                arg("create"),
+                arg("truncate"),
+                r.body.clone().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let call = |offset: &str, first: &str, body: &[u8]| {
+        (
+            offset.to_string(),
+            first.to_string(),
+            first.to_string(),
+            body.to_vec(),
+        )
+    };
+    assert_eq!(
+        calls,
+        [
+            call("0", "true", b"0123"),
+            call("4", "false", b"4567"),
+            call("8", "false", b"89"),
+        ]
+    );
+    assert_eq!(*progress.borrow(), [4, 8, 10]);
+}
+
+#[test]
+fn empty_writer_still_creates_the_file() {
+    let client = IpfsClient::with_transport(MockTransport::new(200, ""));
+    let writer = client.file_writer(FileWriteOptions::new("/foo/empty"));
+    assert_eq!(writer.finish().unwrap(), 0);
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].body.as_deref(), Some(&b""[..]));
+}
+
+#[test]
+fn failed_finish_is_not_resent_on_drop() {
+    use std::io::Write;
+
+    let client = IpfsClient::with_transport(MockTransport::new(200, ""));
+    let mut writer = client.file_writer(FileWriteOptions::new("/foo/bar.txt"));
+    writer.write_all(b"abc").unwrap();
+    client.transport().failures.set(1);
+    assert!(matches!(writer.finish(), Err(IPFSErrorKind::RequestError)));
+    assert_eq!(client.transport().requests.borrow().len(), 1);
+
+    // a writer that is only dropped still sends its last chunk
+    let mut writer = client.file_writer(FileWriteOptions::new("/foo/baz.txt"));
+    writer.write_all(b"abc").unwrap();
+    drop(writer);
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].body.as_deref(), Some(&b"abc"[..]));
+}
+
+// Serves `files/stat` and `files/read` for a single file.
+fn file_client(content: Vec<u8>) -> IpfsClient<MockTransport> {
+    IpfsClient::with_transport(MockTransport::with_responder(move |request| {
+        let body = match request.api() {
+            "files/stat" => format!(
+                r#"{{"Hash":"QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn","Size":{},"Blocks":1,"Type":"file","CumulativeSize":{}}}"#,
+                content.len(),
+                content.len()
+            )
+            .into_bytes(),
+            _ => {
+                let arg = |name: &str| -> usize { request.arg(name).unwrap().parse().unwrap() };
+                let start = arg("offset").min(content.len());
+                let end = (start + arg("count")).min(content.len());
+                content[start..end].to_vec()
+            }
+        };
+        (200, body)
+    }))
+}
+
+#[test]
+fn reads_files_in_windows() {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let content: Vec<u8> = (0..10_000_000u32).map(|i| i as u8).collect();
+    let client = file_client(content.clone());
+    assert_eq!(client.read_entire_file("/big.bin").unwrap(), content);
+    // one stat and three 4MiB windows
+    assert_eq!(client.transport().requests.borrow().len(), 4);
+
+    let mut reader = client.open_file("/big.bin").unwrap();
+    reader.set_window_size(1000);
+    let mut buf = [0u8; 10];
+    reader.seek(SeekFrom::End(-5)).unwrap();
+    assert_eq!(reader.read(&mut buf).unwrap(), 5);
+    assert_eq!(buf[..5], content[content.len() - 5..]);
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+    reader.seek(SeekFrom::Start(2000)).unwrap();
+    let mut small = Vec::new();
+    reader.by_ref().take(1500).read_to_end(&mut small).unwrap();
+    assert_eq!(small, content[2000..3500]);
+    assert_eq!(reader.stream_position().unwrap(), 3500);
+    assert!(reader.seek(SeekFrom::Current(-4000)).is_err());
+
+    assert_eq!(reader.read_range(10, 2500).unwrap(), content[10..2510]);
+}
+
+#[test]
+fn stats_with_locality() {
+    let body = r#"{"Hash":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o","Size":12,"CumulativeSize":20,"Blocks":0,"Type":"file","WithLocality":true,"Local":true,"SizeLocal":20}"#;
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let opts = FileStatOptions {
+        with_local: true,
+        ..Default::default()
+    };
+    let stat = client.stat("/foo/bar.txt", &opts).unwrap();
+    assert_eq!(
+        stat.hash().to_string(),
+        "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"
+    );
+    assert_eq!((stat.size(), stat.cumulative_size()), (12, 20));
+    assert!(!stat.is_dir());
+    assert_eq!(stat.local(), Some(true));
+    assert_eq!(stat.size_local(), Some(20));
+    let requests = client.transport().requests.borrow();
+    assert_eq!(
+        requests[0].args[1],
+        ("with-local".to_string(), "true".to_string())
+    );
+}
+
+#[test]
+fn moves_flushes_and_changes_cids() {
+    let body = r#"{"Cid":"QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"}"#;
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    client.rename("/foo/a.txt", "/foo/b.txt").unwrap();
+    let cid = client.flush(Some("/foo")).unwrap();
+    assert_eq!(
+        cid.to_string(),
+        "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+    );
+    let opts = ChangeCidOptions {
+        cid_version: Some(1),
+        hash: None,
+    };
+    client.change_cid("/foo", &opts).unwrap();
+
+    let requests = client.transport().requests.borrow();
+    let apis = requests.iter().map(|r| r.api.as_str()).collect::<Vec<_>>();
+    assert_eq!(apis, ["files/mv", "files/flush", "files/chcid"]);
+    assert_eq!(requests[0].args.len(), 2);
+    assert_eq!(
+        requests[2].args[1],
+        ("cid-version".to_string(), "1".to_string())
+    );
+}
+
+const HELLO: &str = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+const EMPTY: &str = "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH";
+const DIR_A: &str = "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn";
+const DIR_B: &str = "QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n";
+
+// Serves directory listings by path, for both `files/ls` and `ls`.
+fn tree_client() -> IpfsClient<MockTransport> {
+    let entry = |name: &str, kind: u8, size: u64, hash: &str| {
+        format!(
+            r#"{{"Name":"{}","Type":{},"Size":{},"Hash":"{}"}}"#,
+            name, kind, size, hash
+        )
+    };
+    let root = format!(
+        "[{},{},{}]",
+        entry("a.txt", 0, 12, HELLO),
+        entry("docs", 1, 0, DIR_A),
+        entry("src", 1, 0, DIR_B)
+    );
+    let dirs = [
+        ("/root".to_string(), root.clone()),
+        (
+            "/root/docs".to_string(),
+            format!("[{}]", entry("readme", 0, 0, EMPTY)),
+        ),
+        (
+            "/root/src".to_string(),
+            format!(
+                "[{},{}]",
+                entry("lib.rs", 0, 12, HELLO),
+                entry("deep", 1, 0, DIR_A)
+            ),
+        ),
+        (
+            "/other".to_string(),
+            format!(
+                "[{},{}]",
+                entry("docs", 1, 0, DIR_A),
+                entry("src", 1, 0, EMPTY)
+            ),
+        ),
+        (
+            "/other/src".to_string(),
+            format!("[{}]", entry("lib.rs", 0, 0, EMPTY)),
+        ),
+        (format!("/ipfs/{}", DIR_B), root),
+    ];
+    IpfsClient::with_transport(MockTransport::with_responder(move |request| {
+        let path = request.arg("arg").unwrap_or("/");
+        let entries = dirs
+            .iter()
+            .find(|(dir, _)| dir == path)
+            .map_or("[]", |(_, entries)| entries.as_str());
+        let body = match request.api() {
+            "files/ls" => format!(r#"{{"Entries":{}}}"#, entries),
+            // `ls` uses unixfs types: 1 for directories, 2 for files
+            _ => format!(
+                r#"{{"Objects":[{{"Hash":"x","Links":{}}}]}}"#,
+                entries.replace(r#""Type":0"#, r#""Type":2"#)
+            ),
+        };
+        (200, body.into_bytes())
+    }))
+}
+
+#[test]
+fn walks_trees_with_full_paths() {
+    let client = tree_client();
+    let paths = client
+        .walk("/root")
+        .map(|entry| entry.unwrap().path)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "/root/a.txt",
+            "/root/docs",
+            "/root/docs/readme",
+            "/root/src",
+            "/root/src/lib.rs",
+            "/root/src/deep",
+        ]
+    );
+
+    let mut walker = client.walk("/root");
+    walker.set_max_depth(1);
+    walker.set_filter(|entry| !entry.is_dir);
+    let files = walker.map(|e| e.unwrap().name).collect::<Vec<_>>();
+    assert_eq!(files, ["a.txt"]);
+
+    let mut walker = client.walk("/root");
+    walker.set_prune(|entry| entry.name == "src");
+    assert_eq!(walker.count(), 4);
+
+    // a bare CID is listed with `ls`
+    let entries = client
+        .walk(DIR_B)
+        .map(|e| e.unwrap())
+        .filter(|e| e.depth == 1)
+        .collect::<Vec<_>>();
+    assert_eq!(entries[0].path, format!("/ipfs/{}/a.txt", DIR_B));
+    assert!(!entries[0].is_dir);
+    assert!(entries[1].is_dir);
+    assert_eq!(client.tree_size("/root").unwrap(), 24);
+}
+
+#[test]
+fn diffs_trees_skipping_identical_subtrees() {
+    let client = tree_client();
+    let changes = client.diff("/root", "/other").unwrap();
+    let summary = changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("a.txt", TreeChangeKind::Removed),
+            ("src/deep", TreeChangeKind::Removed),
+            ("src/lib.rs", TreeChangeKind::Modified),
+        ]
+    );
+    // `docs` has the same CID on both sides
+    assert!(!client
+        .transport()
+        .requests
+        .borrow()
+        .iter()
+        .any(|r| r.args.iter().any(|(_, value)| value.ends_with("docs"))));
+}
+
+#[test]
+fn puts_and_gets_dag_nodes() {
+    let cid = "bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua";
+    let body = format!(r#"{{"Cid":{{"/":"{}"}},"RemPath":"items/0"}}"#, cid);
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let node = Ipld::Map(Default::default());
+    let put = client.dag_put(&node, &DagPutOptions::default()).unwrap();
+    assert_eq!(put.to_string(), cid);
+    let resolved = client.dag_resolve(&format!("{}/items/0", cid)).unwrap();
+    assert_eq!(resolved.cid, put);
+    assert_eq!(resolved.rem_path, "items/0");
+
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests[0].api, "dag/put");
+    assert_eq!(
+        requests[0].args,
+        [
+            ("store-codec".to_string(), "dag-cbor".to_string()),
+            ("input-codec".to_string(), "dag-json".to_string()),
+        ]
+    );
+    assert_eq!(requests[0].body.as_deref(), Some(&b"{}"[..]));
+
+    let body = r#"{"a":[1,{"/":{"bytes":"AQI"}}]}"#;
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let node = client.dag_get(&format!("{}/a", cid)).unwrap();
+    assert_eq!(node.get_path("a/1"), Some(&Ipld::Bytes(vec![1, 2])));
+
+    let body = "{\"Size\":10,\"NumBlocks\":1}\n{\"DagStats\":[{\"Size\":42,\"NumBlocks\":3}]}\n";
+    let client = IpfsClient::with_transport(MockTransport::new(200, body));
+    let stat = client.dag_stat(&put).unwrap();
+    assert_eq!((stat.size, stat.num_blocks), (42, 3));
+}
+
+#[test]
+fn reports_ipld_errors() {
+    let client = IpfsClient::with_transport(MockTransport::new(200, "{\"a\":"));
+    let result = client.dag_get("bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua");
+    let Err(IPFSErrorKind::Ipld(e)) = result else {
+        panic!("{:?}", result);
+    };
+    assert!(matches!(e, IpldErrorKind::InvalidDagJson(_)));
+
+    let result = client.dag_put(&Ipld::Float(f64::NAN), &DagPutOptions::default());
+    assert!(matches!(
+        result,
+        Err(IPFSErrorKind::Ipld(IpldErrorKind::UnsupportedValue(_)))
+    ));
+    let error = result.unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("IPLD error: Unsupported value: "));
+    assert!(std::error::Error::source(&error).is_none());
+    assert_eq!(u32::from(&error), 4);
+    // nothing was sent for the node that couldn't be encoded
+    assert_eq!(client.transport().requests.borrow().len(), 1);
+}
+
+#[test]
+fn reads_into_caller_buffers() {
+    let client = IpfsClient::with_transport(MockTransport::new(200, "content"));
+    let mut buf = b"head:".to_vec();
+    let opts = CatOptions {
+        offset: 0,
+        length: Some(7),
+    };
+    let num = client
+        .cat_into(
+            "/ipfs/QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn",
+            &opts,
+            &ReadLoop::new(),
+            &mut buf,
+        )
+        .unwrap();
+    assert_eq!(num, 7);
+    assert_eq!(buf, b"head:content");
+
+    let body = r#"{"Message":"file does not exist","Code":0}"#;
+    let client = IpfsClient::with_transport(MockTransport::new(500, body));
+    let opts = FileReadOptions {
+        offset: 0,
+        count: None,
+    };
+    let result = client.read_into("/missing", &opts, &ReadLoop::new(), &mut buf);
+    assert!(matches!(result, Err(IPFSErrorKind::ApiError { .. })));
+    // the error body doesn't end up in the caller's buffer
+    assert_eq!(buf, b"head:content");
+}
+
+#[test]
+fn redacts_secret_args() {
+    let key = Secret::from("sk-remote-pin-key");
+    let mut request = IpfsRequest::new("pin/remote/service/add");
+    request.add_arg("arg", "pinata");
+    request.add_secret_arg("arg", &key);
+    let debug = format!("{:?}", request);
+    assert!(!debug.contains("sk-remote-pin-key"), "{}", debug);
+    assert!(debug.contains("pinata"));
+    assert_eq!(request.args().nth(1), Some(("arg", "sk-remote-pin-key")));
+    assert!(request.to_json().contains("sk-remote-pin-key"));
+
+    let client = IpfsClient::with_transport(MockTransport::new(200, ""));
+    client
+        .pin_remote_service_add("pinata", "https://api.pinata.cloud/psa", &key)
+        .unwrap();
+    let requests = client.transport().requests.borrow();
+    assert_eq!(
+        requests[0].args[2],
+        ("arg".to_string(), "sk-remote-pin-key".to_string())
+    );
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use blockless_sdk::*;
+
+fn sample() -> Ipld {
+    let link: Cid = "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+        .parse()
+        .unwrap();
+    let mut map = BTreeMap::new();
+    map.insert("name".to_string(), Ipld::from("blockless"));
+    map.insert("count".to_string(), Ipld::from(-3i64));
+    map.insert("ratio".to_string(), Ipld::from(1.5));
+    map.insert("data".to_string(), Ipld::from(vec![0u8, 1, 2, 255]));
+    map.insert(
+        "items".to_string(),
+        Ipld::List(vec![Ipld::Null, Ipld::Bool(true), Ipld::Link(link)]),
+    );
+    Ipld::Map(map)
+}
+
+#[test]
+fn round_trips_both_codecs() {
+    let node = sample();
+    for codec in [DagCodec::DagJson, DagCodec::DagCbor] {
+        let bytes = node.encode(codec).unwrap();
+        assert_eq!(Ipld::decode(codec, &bytes).unwrap(), node);
+    }
+}
+
+#[test]
+fn encodes_canonical_dag_json() {
+    let json = String::from_utf8(sample().encode(DagCodec::DagJson).unwrap()).unwrap();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"count":-3,"data":{"/":{"bytes":"AAEC/w"}},"#,
+            r#""items":[null,true,{"/":"QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"}],"#,
+            r#""name":"blockless","ratio":1.5}"#
+        )
+    );
+}
+
+#[test]
+fn sorts_dag_cbor_keys_by_length_first() {
+    let mut map = BTreeMap::new();
+    map.insert("bb".to_string(), Ipld::from(1u32));
+    map.insert("c".to_string(), Ipld::from(2u32));
+    let bytes = Ipld::Map(map).encode(DagCodec::DagCbor).unwrap();
+    assert_eq!(bytes, [0xa2, 0x61, b'c', 0x02, 0x62, b'b', b'b', 0x01]);
+
+    let empty = Ipld::Map(BTreeMap::new());
+    assert_eq!(
+        empty.cid(DagCodec::DagCbor).unwrap().to_string(),
+        "bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua"
+    );
+}
+
+#[test]
+fn finds_links_and_paths() {
+    let node = sample();
+    assert_eq!(node.links().len(), 1);
+    assert_eq!(node.get_path("items/1"), Some(&Ipld::Bool(true)));
+    assert!(node.get_path("items/2").unwrap().as_link().is_some());
+    assert_eq!(node.get_path("missing/0"), None);
+}
+
+#[test]
+fn rejects_maps_that_read_back_as_links() {
+    for value in [
+        Ipld::from("x"),
+        Ipld::Map(BTreeMap::from([("bytes".to_string(), Ipld::from("AQI"))])),
+    ] {
+        let node = Ipld::Map(BTreeMap::from([("/".to_string(), value)]));
+        assert!(matches!(
+            node.encode(DagCodec::DagJson),
+            Err(IpldErrorKind::UnsupportedValue(_))
+        ));
+        // dag-cbor has real links and bytes, so the map is fine there
+        let bytes = node.encode(DagCodec::DagCbor).unwrap();
+        assert_eq!(Ipld::decode(DagCodec::DagCbor, &bytes).unwrap(), node);
+    }
+    // "/" next to other keys is an ordinary map
+    let node = Ipld::Map(BTreeMap::from([
+        ("/".to_string(), Ipld::from("x")),
+        ("a".to_string(), Ipld::Null),
+    ]));
+    let bytes = node.encode(DagCodec::DagJson).unwrap();
+    assert_eq!(bytes, br#"{"/":"x","a":null}"#);
+    assert_eq!(Ipld::decode(DagCodec::DagJson, &bytes).unwrap(), node);
+}
+
+#[test]
+fn rejects_non_canonical_dag_cbor() {
+    // duplicate keys
+    let bytes = [0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02];
+    assert!(matches!(
+        Ipld::decode(DagCodec::DagCbor, &bytes),
+        Err(IpldErrorKind::InvalidDagCbor(_))
+    ));
+    // trailing bytes
+    assert!(Ipld::decode(DagCodec::DagCbor, &[0xf6, 0x00]).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn round_trips_structs() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Post {
+        title: String,
+        tags: Vec<String>,
+        parent: Option<Cid>,
+        score: u32,
+    }
+
+    let post = Post {
+        title: "hello".to_string(),
+        tags: vec!["a".to_string(), "b".to_string()],
+        parent: Some(
+            "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+                .parse()
+                .unwrap(),
+        ),
+        score: 7,
+    };
+    let node = to_ipld(&post).unwrap();
+    assert!(node.get("parent").unwrap().as_link().is_some());
+    let bytes = node.encode(DagCodec::DagCbor).unwrap();
+    let decoded = Ipld::decode(DagCodec::DagCbor, &bytes).unwrap();
+    assert_eq!(from_ipld::<Post>(decoded).unwrap(), post);
+}
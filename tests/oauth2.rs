@@ -0,0 +1,216 @@
+use std::{cell::RefCell, time::Duration};
+
+use blockless_sdk::{oauth2::*, CodeStatus, HttpErrorKind, HttpOptions, OAuth2ErrorKind};
+
+// Answers every token request with the same status and body and records
+// the url and form of each request.
+struct MockTransport {
+    status: CodeStatus,
+    body: &'static str,
+    requests: RefCell<Vec<(String, String)>>,
+}
+
+impl MockTransport {
+    fn new(body: &'static str) -> Self {
+        Self::with_status(200, body)
+    }
+
+    fn with_status(status: CodeStatus, body: &'static str) -> Self {
+        MockTransport {
+            status,
+            body,
+            requests: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.requests.borrow().len()
+    }
+
+    fn form(&self, index: usize) -> String {
+        self.requests.borrow()[index].1.clone()
+    }
+}
+
+impl TokenTransport for &MockTransport {
+    fn post_form(&self, url: &str, form: &str) -> Result<(CodeStatus, Vec<u8>), HttpErrorKind> {
+        self.requests
+            .borrow_mut()
+            .push((url.to_string(), form.to_string()));
+        Ok((self.status, self.body.as_bytes().to_vec()))
+    }
+}
+
+fn config() -> OAuth2Config {
+    OAuth2Config::new("https://auth.example.com/token", "client")
+}
+
+#[test]
+fn encodes_client_credentials_requests() {
+    let mut config = config();
+    config.set_client_secret("s3cr&t =/+");
+    config.add_scope("read");
+    config.add_scope("write:all");
+    assert!(!format!("{:?}", config).contains("s3cr&t"));
+
+    let transport = MockTransport::new(r#"{"access_token":"a","token_type":"bearer"}"#);
+    let mut client = OAuth2Client::with_transport(config, &transport);
+    let token = client.client_credentials().unwrap();
+    assert_eq!(token.access_token, "a");
+    assert_eq!(token.token_type, "bearer");
+    assert_eq!(
+        transport.requests.borrow()[0],
+        (
+            "https://auth.example.com/token".to_string(),
+            "grant_type=client_credentials&scope=read+write%3Aall&client_id=client\
+             &client_secret=s3cr%26t+%3D%2F%2B"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn uses_the_refresh_token_grant() {
+    let transport = MockTransport::new(r#"{"access_token":"a","refresh_token":"r1"}"#);
+    let mut client = OAuth2Client::with_transport(config(), &transport);
+    assert!(matches!(
+        client.refresh(),
+        Err(OAuth2ErrorKind::MissingRefreshToken)
+    ));
+    assert_eq!(transport.calls(), 0);
+
+    client.set_refresh_token("r 0");
+    client.token().unwrap();
+    assert_eq!(
+        transport.form(0),
+        "grant_type=refresh_token&refresh_token=r+0&client_id=client"
+    );
+    // the refresh token from the response replaces the one set
+    client.invalidate();
+    client.token().unwrap();
+    assert_eq!(
+        transport.form(1),
+        "grant_type=refresh_token&refresh_token=r1&client_id=client"
+    );
+}
+
+#[test]
+fn switches_to_refresh_tokens_from_responses() {
+    let transport = MockTransport::new(r#"{"access_token":"a","refresh_token":"r1"}"#);
+    let mut client = OAuth2Client::with_transport(config(), &transport);
+    client.token().unwrap();
+    client.invalidate();
+    client.token().unwrap();
+    assert_eq!(
+        transport.form(0),
+        "grant_type=client_credentials&client_id=client"
+    );
+    assert_eq!(
+        transport.form(1),
+        "grant_type=refresh_token&refresh_token=r1&client_id=client"
+    );
+}
+
+#[test]
+fn sets_the_authorization_header() {
+    let transport = MockTransport::new(r#"{"access_token":"tok-1","token_type":"Bearer"}"#);
+    let mut client = OAuth2Client::with_transport(config(), &transport);
+    let mut opts = HttpOptions::new("GET", 30, 10);
+    opts.set_header("Accept", "application/json");
+    client.authorize(&mut opts).unwrap();
+    assert_eq!(opts.headers["Authorization"], "Bearer tok-1");
+    assert_eq!(opts.headers["Accept"], "application/json");
+    assert!(opts.dump().contains("Bearer tok-1"));
+
+    // the cached token is reused
+    client.authorize(&mut opts).unwrap();
+    assert_eq!(transport.calls(), 1);
+}
+
+#[test]
+fn parses_token_errors() {
+    let transport = MockTransport::with_status(
+        401,
+        r#"{"error":"invalid_client","error_description":"Client authentication failed"}"#,
+    );
+    let mut client = OAuth2Client::with_transport(config(), &transport);
+    let e = client.token().unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "Token request failed with status 401: invalid_client (Client authentication failed)"
+    );
+    assert!(matches!(
+        e,
+        OAuth2ErrorKind::TokenRequestFailed {
+            status: 401,
+            error: Some(_),
+            description: Some(_),
+        }
+    ));
+    // nothing is cached after a failure
+    assert!(client.token().is_err());
+    assert_eq!(transport.calls(), 2);
+
+    for body in ["<html>Bad Gateway</html>", r#"{"error": 42}"#, ""] {
+        let transport = MockTransport {
+            status: 502,
+            body,
+            requests: RefCell::new(Vec::new()),
+        };
+        let mut client = OAuth2Client::with_transport(config(), &transport);
+        let e = client.token().unwrap_err();
+        assert!(matches!(
+            e,
+            OAuth2ErrorKind::TokenRequestFailed {
+                status: 502,
+                error: None,
+                description: None,
+            }
+        ));
+        assert_eq!(e.to_string(), "Token request failed with status 502");
+    }
+}
+
+#[test]
+fn rejects_responses_without_an_access_token() {
+    for body in [
+        r#"{"token_type":"Bearer"}"#,
+        "not json",
+        r#"{"access_token":1}"#,
+    ] {
+        let transport = MockTransport::new(body);
+        let mut client = OAuth2Client::with_transport(config(), &transport);
+        assert!(matches!(
+            client.token(),
+            Err(OAuth2ErrorKind::InvalidResponse)
+        ));
+    }
+}
+
+#[test]
+fn huge_expires_in_means_no_expiry() {
+    let transport = MockTransport::new(r#"{"access_token":"a","expires_in":18446744073709551615}"#);
+    let mut client = OAuth2Client::with_transport(config(), &transport);
+    let token = client.token().unwrap();
+    assert!(token.expires_at.is_none());
+    assert!(!token.is_expired(Duration::from_secs(u64::MAX)));
+}
+
+#[test]
+fn refetches_tokens_expiring_within_the_skew() {
+    let transport = MockTransport::new(r#"{"access_token":"a","expires_in":"60"}"#);
+    let mut client = OAuth2Client::with_transport(config(), &transport);
+    let token = client.token().unwrap();
+    assert!(token.expires_at.is_some());
+    assert!(!token.is_expired(Duration::from_secs(30)));
+    assert!(token.is_expired(Duration::from_secs(60)));
+
+    // cached while outside the skew
+    client.token().unwrap();
+    assert_eq!(transport.calls(), 1);
+
+    client.set_expiry_skew(Duration::from_secs(120));
+    client.token().unwrap();
+    client.token().unwrap();
+    assert_eq!(transport.calls(), 3);
+}
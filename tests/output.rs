@@ -0,0 +1,121 @@
+use std::{cell::RefCell, io::Write, process::ExitCode, rc::Rc};
+
+use blockless_sdk::{host_call_count, output::*};
+use serde_json::{json, Value};
+
+// Collects what an `Output` writes.
+#[derive(Clone, Default)]
+struct Captured(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn captured(format: Format) -> (Output, Captured) {
+    let captured = Captured::default();
+    let mut output = Output::new(format);
+    output.set_writer(captured.clone());
+    (output, captured)
+}
+
+fn json_envelope(captured: &Captured) -> Value {
+    let bytes = captured.0.borrow();
+    assert_eq!(bytes.last(), Some(&b'\n'));
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+// Checks the metadata every envelope carries and returns the rest of it.
+fn check_metadata(envelope: &mut Value) -> Value {
+    let mut metadata = envelope["metadata"].take();
+    assert!(metadata["duration_ms"].is_u64());
+    assert_eq!(metadata["host_calls"], json!(host_call_count()));
+    let metadata = metadata.as_object_mut().unwrap();
+    metadata.remove("duration_ms");
+    metadata.remove("host_calls");
+    Value::Object(metadata.clone())
+}
+
+#[test]
+fn exit_status_follows_the_result() {
+    let ok: Result<u32, String> = Ok(1);
+    assert_eq!(captured(Format::Json).0.finish(ok), ExitCode::SUCCESS);
+    let failed: Result<u32, String> = Err("no price".to_string());
+    assert_eq!(
+        captured(Format::Json).0.finish(failed),
+        ExitCode::from(EXIT_FAILURE)
+    );
+}
+
+#[test]
+fn writes_success_envelopes() {
+    let (mut output, captured) = captured(Format::Json);
+    output.set_metadata("source", "coingecko");
+    let data = json!({"id": "bitcoin", "price": 67675000000u64});
+    assert_eq!(output.finish(Ok::<_, String>(&data)), ExitCode::SUCCESS);
+
+    let mut envelope = json_envelope(&captured);
+    assert_eq!(
+        check_metadata(&mut envelope),
+        json!({"source": "coingecko"})
+    );
+    assert_eq!(
+        envelope,
+        json!({"status": "ok", "data": data, "error": null, "metadata": null})
+    );
+}
+
+#[test]
+fn writes_failure_envelopes() {
+    let (output, captured) = captured(Format::Json);
+    assert_eq!(
+        output.fail("Coin not found", EXIT_INPUT_ERROR),
+        ExitCode::from(EXIT_INPUT_ERROR)
+    );
+    let mut envelope = json_envelope(&captured);
+    assert_eq!(check_metadata(&mut envelope), json!({}));
+    assert_eq!(
+        envelope,
+        json!({
+            "status": "error",
+            "data": null,
+            "error": {"message": "Coin not found"},
+            "metadata": null
+        })
+    );
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn writes_cbor_envelopes() {
+    let (output, captured) = captured(Format::Cbor);
+    assert_eq!(output.succeed(&[1, 2, 3]), ExitCode::SUCCESS);
+    let bytes = captured.0.borrow();
+    let mut envelope: Value = ciborium::from_reader(&bytes[..]).unwrap();
+    assert_eq!(check_metadata(&mut envelope), json!({}));
+    assert_eq!(
+        envelope,
+        json!({"status": "ok", "data": [1, 2, 3], "error": null, "metadata": null})
+    );
+}
+
+#[cfg(not(feature = "cbor"))]
+#[test]
+fn unencodable_output_is_a_failure() {
+    // cbor output can't be encoded without the cbor feature
+    let (output, captured) = captured(Format::Cbor);
+    assert_eq!(output.succeed(&1), ExitCode::from(EXIT_FAILURE));
+    let mut envelope = json_envelope(&captured);
+    check_metadata(&mut envelope);
+    assert_eq!(envelope["status"], "error");
+    assert_eq!(
+        envelope["error"]["message"],
+        "Encode output error: cbor output requires the cbor feature"
+    );
+}
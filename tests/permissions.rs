@@ -0,0 +1,90 @@
+use blockless_sdk::permissions::Permission;
+
+#[cfg(feature = "ipfs")]
+mod common;
+
+fn url(url: &str) -> Permission {
+    Permission::Url(url.to_string())
+}
+
+fn ipfs(path: &str) -> Permission {
+    Permission::IpfsPath(path.to_string())
+}
+
+#[test]
+fn matches_prefixes_at_boundaries() {
+    assert!(url("https://a.com/api").allows(&url("https://a.com/api/v1?q=1")));
+    assert!(url("https://a.com/api").allows(&url("https://a.com/api#top")));
+    assert!(!url("https://a.com").allows(&url("https://a.com.evil.com")));
+    assert!(!url("https://a.com/api").allows(&url("https://a.com/apiv2")));
+    assert!(ipfs("/data").allows(&ipfs("/data/a.txt")));
+    assert!(!ipfs("/data").allows(&ipfs("/database")));
+}
+
+#[test]
+fn rejects_dot_segments_in_urls() {
+    let api = url("https://a.com/api");
+    for requested in [
+        "https://a.com/api/../admin",
+        "https://a.com/api/./v1",
+        "https://a.com/api/%2e%2e/admin",
+        "https://a.com/api/.%2E/admin",
+        "https://a.com/api\\..\\admin",
+        "https://a.com/api/..",
+    ] {
+        assert!(!api.allows(&url(requested)), "{}", requested);
+    }
+    // dots in the query or a file name are not segments
+    assert!(api.allows(&url("https://a.com/api/v1?path=../x")));
+    assert!(api.allows(&url("https://a.com/api/a..b.json")));
+    assert!(!url("https://a.com/api/../admin").allows(&url("https://a.com/api/../admin")));
+}
+
+#[test]
+fn rejects_dot_segments_in_ipfs_paths() {
+    let data = ipfs("/data");
+    for requested in [
+        "/data/../secrets",
+        "/data/./a.txt",
+        "/data/%2E%2E/secrets",
+        "/data/..",
+    ] {
+        assert!(!data.allows(&ipfs(requested)), "{}", requested);
+    }
+    assert!(data.allows(&ipfs("/data/.hidden")));
+    assert!(!ipfs("/data/..").allows(&ipfs("/data/../secrets")));
+}
+
+#[cfg(feature = "ipfs")]
+#[test]
+fn copy_from_cid_needs_the_destination_only() {
+    use blockless_sdk::{permissions::Manifest, *};
+
+    let client = IpfsClient::with_transport(common::MockTransport::new(200, ""));
+    let cid: Cid = "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+        .parse()
+        .unwrap();
+    let opts = FileCopyOptions { parents: false };
+    let mut manifest = Manifest::new();
+    manifest.allow_ipfs_path("/data");
+    manifest.install();
+
+    let copied = client.copy_from_cid(&cid, "/data/tree", &opts);
+    let outside = client.copy_from_cid(&cid, "/secrets/tree", &opts);
+    let copy = client.copy(&format!("/ipfs/{}", cid), "/data/tree", &opts);
+    permissions::uninstall();
+
+    assert!(copied.is_ok());
+    assert!(matches!(
+        outside,
+        Err(IPFSErrorKind::MissingPermission(Permission::IpfsPath(path))) if path == "/secrets/tree"
+    ));
+    // copying an MFS or `/ipfs/` path still needs both
+    assert!(matches!(copy, Err(IPFSErrorKind::MissingPermission(_))));
+    let requests = client.transport().requests.borrow();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].args[0],
+        ("arg".to_string(), format!("/ipfs/{}", cid))
+    );
+}
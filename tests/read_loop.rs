@@ -0,0 +1,109 @@
+use blockless_sdk::*;
+
+// Serves `data` in reads of at most `max_read` bytes and records the size
+// of every buffer it was given and what it returned.
+struct Source {
+    data: Vec<u8>,
+    pos: usize,
+    max_read: usize,
+    calls: Vec<(usize, usize)>,
+}
+
+impl Source {
+    fn new(len: usize, max_read: usize) -> Self {
+        Source {
+            data: (0..len).map(|i| i as u8).collect(),
+            pos: 0,
+            max_read,
+            calls: Vec::new(),
+        }
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+        let num = buf.len().min(self.max_read).min(self.data.len() - self.pos);
+        self.calls.push((buf.len(), num));
+        buf[..num].copy_from_slice(&self.data[self.pos..self.pos + num]);
+        self.pos += num;
+        Ok(num)
+    }
+}
+
+#[test]
+fn doubles_chunks_without_a_hint() {
+    let mut source = Source::new(10000, usize::MAX);
+    let mut read_loop = ReadLoop::new();
+    read_loop.set_chunk_size(1024, 4096);
+    let mut buf = Vec::new();
+    let num = read_loop
+        .read_to_end(&mut buf, |chunk| source.read(chunk))
+        .unwrap();
+    assert_eq!(num, 10000);
+    assert_eq!(buf, source.data);
+    let sizes = source.calls.iter().map(|c| c.0).collect::<Vec<_>>();
+    assert_eq!(sizes[..4], [1024, 2048, 4096, 4096]);
+}
+
+#[test]
+fn exact_hint_reads_in_one_call_and_one_probe() {
+    let mut source = Source::new(100000, usize::MAX);
+    let mut read_loop = ReadLoop::new();
+    read_loop.set_size_hint(100000);
+    let mut buf = Vec::new();
+    read_loop
+        .read_to_end(&mut buf, |chunk| source.read(chunk))
+        .unwrap();
+    assert_eq!(buf, source.data);
+    assert_eq!(source.calls.len(), 2);
+    assert_eq!(source.calls[0], (100000, 100000));
+    // the probe that finds the end goes through a small stack buffer, so the
+    // pre-sized buffer is never grown
+    assert!(source.calls[1].0 <= 64);
+    assert_eq!(buf.capacity(), 100000);
+}
+
+#[test]
+fn short_hint_keeps_reading_past_it() {
+    let mut source = Source::new(5000, usize::MAX);
+    let mut read_loop = ReadLoop::new();
+    read_loop.set_size_hint(1000);
+    let mut buf = vec![9];
+    let num = read_loop
+        .read_to_end(&mut buf, |chunk| source.read(chunk))
+        .unwrap();
+    assert_eq!(num, 5000);
+    assert_eq!(buf[0], 9);
+    assert_eq!(buf[1..], source.data);
+}
+
+#[test]
+fn short_final_read_ends_at_zero() {
+    // the host returns less than asked before the end of data
+    let mut source = Source::new(3000, 700);
+    let mut read_loop = ReadLoop::new();
+    read_loop.set_chunk_size(1024, 1024);
+    let mut buf = Vec::new();
+    read_loop
+        .read_to_end(&mut buf, |chunk| source.read(chunk))
+        .unwrap();
+    assert_eq!(buf, source.data);
+    // short reads don't end the loop, only the read that returns 0 does
+    let ends = source.calls.iter().filter(|c| c.1 == 0).count();
+    assert_eq!(ends, 1);
+    assert_eq!(source.calls.last().unwrap().1, 0);
+}
+
+#[test]
+fn error_keeps_earlier_data() {
+    let mut calls = 0;
+    let mut buf = Vec::new();
+    let result = ReadLoop::new().read_to_end(&mut buf, |chunk| {
+        calls += 1;
+        if calls > 1 {
+            return Err("broken");
+        }
+        chunk[..3].copy_from_slice(b"abc");
+        Ok(3)
+    });
+    assert_eq!(result, Err("broken"));
+    assert_eq!(buf, b"abc");
+}
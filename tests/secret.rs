@@ -0,0 +1,46 @@
+use blockless_sdk::{env::Env, Secret};
+
+const VALUE: &str = "hunter2-7f3a";
+
+fn assert_redacted(formatted: String) {
+    assert!(!formatted.contains(VALUE), "{}", formatted);
+}
+
+#[test]
+fn secrets_are_redacted() {
+    let secret = Secret::from(VALUE.to_string());
+    assert_eq!(format!("{:?}", secret), "[REDACTED]");
+    assert_eq!(format!("{}", secret), "[REDACTED]");
+    assert_redacted(format!("{:#?} {:>20}", secret, secret));
+    assert_redacted(format!("{:?}", Some(secret.clone())));
+    assert_eq!(secret.expose_secret(), VALUE);
+}
+
+#[test]
+fn env_debug_shows_names_only() {
+    let env = Env::parse(format!("API_KEY={}\nOTHER=1", VALUE).as_bytes()).unwrap();
+    let debug = format!("{:?}", env);
+    assert!(debug.contains("API_KEY"), "{}", debug);
+    assert_redacted(debug);
+    assert_redacted(format!("{:#?}", env));
+    assert_redacted(format!("{:?}", env.require_secret("API_KEY").unwrap()));
+
+    let env = Env::parse(format!(r#"{{"API_KEY": "{}"}}"#, VALUE).as_bytes()).unwrap();
+    assert_redacted(format!("{:?}", env));
+}
+
+#[cfg(feature = "s3")]
+#[test]
+fn s3_configure_debug_hides_the_keys() {
+    let mut config = blockless_sdk::AWSS3Configure::new(
+        format!("AKIA{}", VALUE),
+        VALUE.to_string(),
+        "https://s3.amazonaws.com".to_string(),
+    );
+    config.set_region("us-east-1".to_string());
+    let debug = format!("{:?}", config);
+    assert!(debug.contains("https://s3.amazonaws.com"), "{}", debug);
+    assert!(debug.contains("us-east-1"), "{}", debug);
+    assert_redacted(debug);
+    assert_redacted(format!("{:#?}", config));
+}
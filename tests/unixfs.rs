@@ -0,0 +1,348 @@
+use std::io::Write;
+
+use blockless_sdk::*;
+
+fn v0(data: &[u8]) -> String {
+    compute_cid(data, &ImportOptions::default()).to_string()
+}
+
+#[test]
+fn matches_ipfs_add_only_hash() {
+    assert_eq!(v0(b""), "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH");
+    assert_eq!(
+        v0(b"hello world\n"),
+        "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"
+    );
+    assert_eq!(
+        DirectoryBuilder::new().finish(Version::V0).cid.to_string(),
+        "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+    );
+}
+
+#[test]
+fn cid_v1_defaults_to_raw_leaves() {
+    let opts = ImportOptions {
+        cid_version: Version::V1,
+        ..Default::default()
+    };
+    // a single raw leaf is the sha2-256 of the content
+    let cid = compute_cid(b"hello world", &opts);
+    assert_eq!(cid.codec(), CODEC_RAW);
+    assert_eq!(
+        cid.to_string(),
+        "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"
+    );
+
+    let opts = ImportOptions {
+        raw_leaves: Some(false),
+        ..opts
+    };
+    let cid = compute_cid(b"hello world\n", &opts);
+    assert_eq!(
+        cid.into_v0().unwrap().to_string(),
+        "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o"
+    );
+}
+
+#[test]
+fn streaming_matches_single_buffer() {
+    let data: Vec<u8> = (0..700_000u32).map(|i| (i * 31 % 251) as u8).collect();
+    for chunker in [Chunker::Size(1024), Chunker::rabin(4096)] {
+        let opts = ImportOptions {
+            chunker,
+            ..Default::default()
+        };
+        let mut importer = UnixfsImporter::new(opts.clone());
+        for piece in data.chunks(777) {
+            importer.write_all(piece).unwrap();
+        }
+        let imported = importer.finish();
+        assert_eq!(imported.file_size, data.len() as u64);
+        assert_eq!(imported.cid, compute_cid(&data, &opts));
+        assert_eq!(
+            compute_cid_from_reader(&data[..], &opts).unwrap(),
+            imported.cid
+        );
+    }
+}
+
+#[test]
+fn parses_chunker_strings() {
+    assert_eq!(Chunker::parse("size-1024"), Some(Chunker::Size(1024)));
+    assert_eq!(Chunker::parse("rabin"), Some(Chunker::rabin(262144)));
+    assert_eq!(
+        Chunker::parse("rabin-1000-2000-3000"),
+        Some(Chunker::Rabin {
+            min: 1000,
+            avg: 2000,
+            max: 3000
+        })
+    );
+    assert_eq!(Chunker::parse("size-0"), None);
+    assert_eq!(Chunker::parse("size-2097152"), None);
+    assert_eq!(Chunker::parse("buzhash"), None);
+    let rabin = Chunker::rabin(4096);
+    assert_eq!(Chunker::parse(&rabin.to_string()), Some(rabin));
+}
+
+// Kubo is not available where these tests run, so the multi-chunk cases
+// can't pin CIDs printed by `ipfs add --only-hash`; `matches_local_kubo`
+// compares against it where it is installed. Otherwise the expected DAG
+// is built below straight from the dag-pb and UnixFS specs, as kubo
+// lays it out: leaves in order, at most 174 links per node, and every
+// level above the leaves made of file nodes, even when a node has one
+// child. The rabin cut points come from the definition of the fingerprint
+// rather than the rolling tables of the importer.
+
+struct Node {
+    cid: Cid,
+    tsize: u64,
+    file_size: u64,
+}
+
+fn varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn field_varint(buf: &mut Vec<u8>, field: u64, value: u64) {
+    varint(buf, field << 3);
+    varint(buf, value);
+}
+
+fn field_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    varint(buf, (field << 3) | 2);
+    varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn dag_pb(block: &[u8], version: Version) -> Cid {
+    let hash = Multihash::sha2_256(block);
+    match version {
+        Version::V0 => Cid::new_v0(hash).unwrap(),
+        Version::V1 => Cid::new_v1(CODEC_DAG_PB, hash),
+    }
+}
+
+fn leaf(chunk: &[u8], version: Version) -> Node {
+    if version == Version::V1 {
+        return Node {
+            cid: Cid::new_v1(CODEC_RAW, Multihash::sha2_256(chunk)),
+            tsize: chunk.len() as u64,
+            file_size: chunk.len() as u64,
+        };
+    }
+    // PBNode { Data: Data { Type: File, Data: chunk, filesize } }
+    let mut data = Vec::new();
+    field_varint(&mut data, 1, 2);
+    field_bytes(&mut data, 2, chunk);
+    field_varint(&mut data, 3, chunk.len() as u64);
+    let mut block = Vec::new();
+    field_bytes(&mut block, 1, &data);
+    Node {
+        cid: dag_pb(&block, version),
+        tsize: block.len() as u64,
+        file_size: chunk.len() as u64,
+    }
+}
+
+fn parent(children: &[Node], version: Version) -> Node {
+    let file_size = children.iter().map(|c| c.file_size).sum();
+    // PBNode { Links: [{ Hash, Name: "", Tsize }], Data: Data { Type: File,
+    // filesize, blocksizes } }, with the links first
+    let mut block = Vec::new();
+    for child in children {
+        let mut link = Vec::new();
+        field_bytes(&mut link, 1, &child.cid.to_bytes());
+        field_bytes(&mut link, 2, b"");
+        field_varint(&mut link, 3, child.tsize);
+        field_bytes(&mut block, 2, &link);
+    }
+    let mut data = Vec::new();
+    field_varint(&mut data, 1, 2);
+    field_varint(&mut data, 3, file_size);
+    for child in children {
+        field_varint(&mut data, 4, child.file_size);
+    }
+    field_bytes(&mut block, 1, &data);
+    Node {
+        cid: dag_pb(&block, version),
+        tsize: block.len() as u64 + children.iter().map(|c| c.tsize).sum::<u64>(),
+        file_size,
+    }
+}
+
+fn reference_dag(chunks: &[&[u8]], version: Version) -> Node {
+    let mut level: Vec<Node> = chunks.iter().map(|c| leaf(c, version)).collect();
+    while level.len() > 1 {
+        level = level.chunks(174).map(|c| parent(c, version)).collect();
+    }
+    level.remove(0)
+}
+
+// xorshift, so the content has no runs the chunkers would treat specially
+fn pseudo_random(len: usize) -> Vec<u8> {
+    let mut x = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x >> 32) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn multi_chunk_files_match_the_reference_dag() {
+    let data = pseudo_random(1 << 20);
+    let chunks: Vec<&[u8]> = data.chunks(262144).collect();
+    for version in [Version::V0, Version::V1] {
+        let opts = ImportOptions {
+            cid_version: version,
+            chunker: Chunker::parse("size-262144").unwrap(),
+            ..Default::default()
+        };
+        let expected = reference_dag(&chunks, version);
+        let mut importer = UnixfsImporter::new(opts);
+        importer.update(&data);
+        let imported = importer.finish();
+        assert_eq!(imported.cid, expected.cid);
+        assert_eq!(imported.size, expected.tsize);
+        assert_eq!(imported.file_size, 1 << 20);
+    }
+    // a 256KiB UnixFS leaf is 262158 bytes, the size `ipfs ls` shows for
+    // each link of such a file, and the root adds 200 bytes
+    let root = reference_dag(&chunks, Version::V0);
+    assert_eq!(leaf(chunks[0], Version::V0).tsize, 262158);
+    assert_eq!(root.tsize, 4 * 262158 + 200);
+}
+
+#[test]
+fn deep_files_match_the_reference_dag() {
+    // one leaf more than a node can link, so the root links two nodes and
+    // the second one has a single child
+    let data = pseudo_random(174 * 1024 + 1);
+    let chunks: Vec<&[u8]> = data.chunks(1024).collect();
+    let opts = ImportOptions {
+        chunker: Chunker::Size(1024),
+        ..Default::default()
+    };
+    assert_eq!(
+        compute_cid(&data, &opts),
+        reference_dag(&chunks, Version::V0).cid
+    );
+}
+
+// The rabin fingerprint of `window`: its bytes read as a polynomial over
+// GF(2), modulo the polynomial of the IPFS rabin chunker.
+fn fingerprint(window: &[u8]) -> u64 {
+    const POLYNOMIAL: u64 = 17437180132763653;
+    let degree = 63 - POLYNOMIAL.leading_zeros();
+    let mut r = 0u64;
+    for byte in window {
+        for bit in (0..8).rev() {
+            r = (r << 1) | (byte >> bit & 1) as u64;
+            if r >> degree & 1 == 1 {
+                r ^= POLYNOMIAL;
+            }
+        }
+    }
+    r
+}
+
+// A chunk ends at the first byte, at least `min` bytes in, where the
+// fingerprint of the last 64 bytes has its low log2(avg) bits clear, or
+// after `max` bytes.
+fn rabin_chunks(data: &[u8], min: usize, avg: usize, max: usize) -> Vec<&[u8]> {
+    let mask = (1u64 << avg.ilog2()) - 1;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let mut end = data.len();
+        for i in start + min - 1..data.len() {
+            let len = i + 1 - start;
+            if len >= max || fingerprint(&data[i + 1 - 64..=i]) & mask == 0 {
+                end = i + 1;
+                break;
+            }
+        }
+        chunks.push(&data[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+#[test]
+fn rabin_files_match_the_reference_dag() {
+    let data = pseudo_random(300_000);
+    for chunker in [Chunker::rabin(4096), Chunker::parse("rabin").unwrap()] {
+        let Chunker::Rabin { min, avg, max } = chunker else {
+            unreachable!()
+        };
+        let chunks = rabin_chunks(&data, min, avg, max);
+        if avg == 4096 {
+            // the fingerprint cuts most chunks, not the max size
+            assert!(chunks.len() > 40);
+            assert!(chunks.iter().filter(|c| c.len() < max).count() > chunks.len() / 2);
+        }
+        let opts = ImportOptions {
+            chunker,
+            ..Default::default()
+        };
+        assert_eq!(
+            compute_cid(&data, &opts),
+            reference_dag(&chunks, Version::V0).cid,
+            "{}",
+            chunker
+        );
+    }
+}
+
+// Runs `ipfs add --only-hash` on the inputs above and compares the CIDs.
+// Needs an initialized kubo repo and `ipfs` on the path:
+//
+//     cargo test --test unixfs -- --ignored --nocapture
+//
+// The CIDs it prints can be pinned in `matches_ipfs_add_only_hash`.
+#[test]
+#[ignore = "needs the ipfs binary"]
+fn matches_local_kubo() {
+    let dir = std::env::temp_dir().join(format!("blockless-unixfs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let cases = [
+        ("size-262144", pseudo_random(1 << 20)),
+        ("rabin", pseudo_random(1 << 20)),
+        ("rabin-4096", pseudo_random(300_000)),
+    ];
+    for (chunker, data) in cases {
+        let path = dir.join(chunker);
+        std::fs::write(&path, &data).unwrap();
+        for (version, flag) in [(Version::V0, "0"), (Version::V1, "1")] {
+            let output = std::process::Command::new("ipfs")
+                .args(["add", "--only-hash", "--quiet", "--cid-version", flag])
+                .arg(format!("--chunker={}", chunker))
+                .arg(&path)
+                .output()
+                .expect("ipfs is not on the path");
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let expected = String::from_utf8(output.stdout).unwrap();
+            let opts = ImportOptions {
+                cid_version: version,
+                chunker: Chunker::parse(chunker).unwrap(),
+                ..Default::default()
+            };
+            let cid = compute_cid(&data, &opts).to_string();
+            println!("{} bytes, {}, cid v{}: {}", data.len(), chunker, flag, cid);
+            assert_eq!(cid, expected.trim(), "{} cid v{}", chunker, flag);
+        }
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}